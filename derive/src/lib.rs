@@ -1,5 +1,5 @@
 use syn::*;
-use quote::quote;
+use quote::{quote, quote_spanned};
 use find_crate::find_crate;
 use proc_macro::TokenStream;
 use proc_macro2::Span;
@@ -68,7 +68,11 @@ impl CrateLocations {
 
 }
 
-#[proc_macro_derive(ConfigActions, attributes(Pressed, JustPressed, Axis))]
+const EVENT_ATTRIBUTES: &[&str] = &[
+    "Pressed", "JustPressed", "JustReleased", "Axis", "DualAxis", "VirtualAxis", "Pointer", "Drag", "Chord", "Sequence", "DoubleTap", "LongPress", "Tap"
+];
+
+#[proc_macro_derive(ConfigActions, attributes(Pressed, JustPressed, JustReleased, Axis, DualAxis, VirtualAxis, Pointer, Drag, Chord, Sequence, DoubleTap, LongPress, Tap))]
 pub fn derive_macro(_input: TokenStream) -> TokenStream {
     let output = quote! {};
     output.into()
@@ -99,7 +103,7 @@ pub fn config_actions(attr: TokenStream, item: TokenStream) -> TokenStream {
                  impl bevy_actions::ConfigActions for #ident {
                      const PATH: &'static str = #file;
 
-                     fn default_bindings() -> #hmap<bevy_actions::Event, Self> {
+                     fn default_bindings() -> #hmap<Self, Vec<bevy_actions::Event>> {
                          #default_impl
                      }
                  }
@@ -139,106 +143,24 @@ fn attribute_inputs(args: Vec<NestedMeta>) -> std::result::Result<(Lit, Lit), To
 }
 
 fn get_default_implementation(input: &syn::ItemEnum, hmap: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
-    use std::str::FromStr;
     let mut variants = vec![];
     let enum_ident = &input.ident;
     for item in input.variants.iter() {
         let enum_item = &item.ident;
         for attr in item.attrs.iter() {
-            let mut attr_str = attr.tokens.to_string();
-            attr_str = attr_str[1..attr_str.len() - 1].to_string();
-            match syn::parse::<syn::MetaList>(TokenStream::from_str(&attr_str).unwrap()) {
-                Err(err) => {
-                    println!("{:?}", err);
-                    return quote!{compile_error!("Failed to parse config_actions attribute arguments")}.into()
-                },
-                Ok(attrs) => {
-                    let mut event_attr: Vec<proc_macro2::TokenStream> = Default::default();
-                    match attr.path.get_ident().unwrap().to_string().as_ref() {
-                        "Pressed" => {
-                            match attrs.path.get_ident().unwrap().to_string().as_ref() {
-                                "Keyboard" => {
-                                    let code = attrs.nested.first().unwrap();
-                                    event_attr.push(quote! {
-                                        Pressed(bevy_actions::Button::Keyboard(KeyCode::#code))
-                                    });
-                                },
-                                "Mouse" => {
-                                    let code = attrs.nested.first().unwrap();
-                                    event_attr.push(quote! {
-                                        Pressed(bevy_actions::Button::Mouse(MouseButton::#code))
-                                    });
-                                },
-                                "Gamepad" => {
-                                    let gamepad = attrs.nested.first().unwrap();
-                                    let axis = attrs.nested.iter().skip(1).next().unwrap();
-                                    let bevy_crate = match find_crate(|s| s == "bevy") {
-                                        Ok(_) => quote! {bevy::input},
-                                        Err(_) => quote ! {bevy_input}
-                                    };
-                                    event_attr.push(quote! {
-                                        Pressed(bevy_actions::Button::Gamepad(#gamepad, #bevy_crate::gamepad::GamepadButtonType::#axis))
-                                    });
-                                },
-                                _ => {}
-                            }
-                        },
-                        "JustPressed" => {
-                            match attrs.path.get_ident().unwrap().to_string().as_ref() {
-                                "Keyboard" => {
-                                    let code = attrs.nested.first().unwrap();
-                                    event_attr.push(quote! {
-                                        JustPressed(bevy_actions::Button::Keyboard(KeyCode::#code))
-                                    });
-                                },
-                                "Mouse" => {
-                                    let code = attrs.nested.first().unwrap();
-                                    event_attr.push(quote! {
-                                        JustPressed(bevy_actions::Button::Mouse(MouseButton::#code))
-                                    });
-                                },
-                                "Gamepad" => {
-                                    let gamepad = attrs.nested.first().unwrap();
-                                    let axis = attrs.nested.iter().skip(1).next().unwrap();
-                                    let bevy_crate = match find_crate(|s| s == "bevy") {
-                                        Ok(_) => quote! {bevy::input},
-                                        Err(_) => quote ! {bevy_input}
-                                    };
-                                    event_attr.push(quote! {
-                                        JustPressed(bevy_actions::Button::Gamepad(#gamepad, #bevy_crate::gamepad::GamepadButtonType::#axis))
-                                    });
-                                },
-                                _ => {}
-                            }
-                        },
-                        "Axis" => {
-                            match attrs.path.get_ident().unwrap().to_string().as_ref() {
-                                "Mouse" => {
-                                    let code = attrs.nested.first().unwrap();
-                                    event_attr.push(quote! {
-                                        Axis(bevy_actions::Axis::Mouse(MouseAxis::#code))
-                                    });
-                                },
-                                "Gamepad" => {
-                                    let gamepad = attrs.nested.first().unwrap();
-                                    let axis = attrs.nested.iter().skip(1).next().unwrap();
-                                    let bevy_crate = match find_crate(|s| s == "bevy") {
-                                        Ok(_) => quote! {bevy::input},
-                                        Err(_) => quote ! {bevy_input}
-                                    };
-                                    event_attr.push(quote! {
-                                        Axis(bevy_actions::Axis::Gamepad(#gamepad, #bevy_crate::gamepad::GamepadAxisType::#axis))
-                                    });
-                                },
-                                _ =>{}
-                            }
-                        },
-                        _ => {}
-                    }
-                    variants.push(quote! {
-                        #(map.insert(bevy_actions::Event::#event_attr, #enum_ident::#enum_item);)*
-                    });
-                }
+            let kind = attr.path.get_ident().map(|x|x.to_string()).unwrap_or_default();
+            if !EVENT_ATTRIBUTES.contains(&kind.as_str()) {
+                continue;
+            }
+            let event = match attr.parse_meta() {
+                Ok(syn::Meta::List(list)) => event_tokens(&list),
+                _ => None
+            };
+            match event {
+                Some(event) => variants.push(quote! {
+                    map.entry(#enum_ident::#enum_item).or_insert_with(Vec::new).push(#event);
+                }),
+                None => return quote!{compile_error!("Failed to parse config_actions attribute arguments")}.into()
             }
         }
     }
@@ -254,3 +176,299 @@ fn get_default_implementation(input: &syn::ItemEnum, hmap: proc_macro2::TokenStr
         output.into()
     }
 }
+
+fn input_crate() -> proc_macro2::TokenStream {
+    match find_crate(|s| s == "bevy") {
+        Ok(_) => quote! {bevy::input},
+        Err(_) => quote ! {bevy_input}
+    }
+}
+
+fn button_tokens(attrs: &syn::MetaList) -> Option<proc_macro2::TokenStream> {
+    let bevy_crate = input_crate();
+    match attrs.path.get_ident()?.to_string().as_ref() {
+        "Keyboard" => {
+            let code = attrs.nested.first()?;
+            Some(quote! { bevy_actions::Button::Keyboard(KeyCode::#code) })
+        },
+        "Mouse" => {
+            let code = attrs.nested.first()?;
+            Some(quote! { bevy_actions::Button::Mouse(MouseButton::#code) })
+        },
+        "Gamepad" => {
+            let gamepad = attrs.nested.first()?;
+            let button = attrs.nested.iter().nth(1)?;
+            Some(quote! {
+                bevy_actions::Button::Gamepad(#gamepad, #bevy_crate::gamepad::GamepadButtonType::#button)
+            })
+        },
+        "Wheel" => {
+            let direction = attrs.nested.first()?;
+            Some(quote! { bevy_actions::Button::Wheel(bevy_actions::WheelDirection::#direction) })
+        },
+        kind @ ("PlayerGamepad" | "AnyGamepad") => {
+            let kind = syn::Ident::new(kind, Span::call_site());
+            let button = attrs.nested.first()?;
+            Some(quote! {
+                bevy_actions::Button::#kind(#bevy_crate::gamepad::GamepadButtonType::#button)
+            })
+        },
+        _ => None
+    }
+}
+
+fn axis_tokens(attrs: &syn::MetaList) -> Option<proc_macro2::TokenStream> {
+    let bevy_crate = input_crate();
+    match attrs.path.get_ident()?.to_string().as_ref() {
+        "Mouse" => {
+            let code = attrs.nested.first()?;
+            Some(quote! { bevy_actions::Axis::Mouse(bevy_actions::MouseAxis::#code) })
+        },
+        "Gamepad" => {
+            let gamepad = attrs.nested.first()?;
+            let axis = attrs.nested.iter().nth(1)?;
+            Some(quote! {
+                bevy_actions::Axis::Gamepad(#gamepad, #bevy_crate::gamepad::GamepadAxisType::#axis)
+            })
+        },
+        kind @ ("PlayerGamepad" | "AnyGamepad") => {
+            let kind = syn::Ident::new(kind, Span::call_site());
+            let axis = attrs.nested.first()?;
+            Some(quote! {
+                bevy_actions::Axis::#kind(#bevy_crate::gamepad::GamepadAxisType::#axis)
+            })
+        },
+        _ => None
+    }
+}
+
+fn float_value(lit: &syn::Lit) -> Option<f32> {
+    let value = match lit {
+        syn::Lit::Float(lit) => lit.base10_parse::<f32>().ok()?,
+        syn::Lit::Int(lit) => lit.base10_parse::<f32>().ok()?,
+        _ => return None
+    };
+    value.is_finite().then(|| value)
+}
+
+fn float_tokens(lit: &syn::Lit) -> Option<proc_macro2::TokenStream> {
+    let lit = proc_macro2::Literal::f32_suffixed(float_value(lit)?);
+    Some(quote! { #lit })
+}
+
+fn processing_tokens<'a>(
+    nested: impl Iterator<Item = &'a syn::NestedMeta>,
+    flags: &[&str],
+    curve: bool
+) -> Option<Vec<proc_macro2::TokenStream>> {
+    nested
+        .map(|nested| match nested {
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) => {
+                let ident = path.get_ident()?;
+                match ident.to_string().as_ref() {
+                    flag if flags.contains(&flag) => Some(quote! { #ident: true }),
+                    "smoothstep" if curve => Some(quote! { curve: bevy_actions::ResponseCurve::Smoothstep }),
+                    _ => None
+                }
+            },
+            syn::NestedMeta::Meta(syn::Meta::NameValue(value)) => {
+                let ident = value.path.get_ident()?;
+                let lit = &value.lit;
+                match ident.to_string().as_ref() {
+                    "outer_dead_zone" => {
+                        let value = float_tokens(lit)?;
+                        Some(quote! { #ident: Some(#value) })
+                    },
+                    "exponent" if curve => match float_value(lit)? {
+                        exponent if exponent > 0.0 => {
+                            let value = proc_macro2::Literal::f32_suffixed(exponent);
+                            Some(quote! { curve: bevy_actions::ResponseCurve::Exponential(#value) })
+                        },
+                        _ => Some(quote_spanned! { lit.span()=>
+                            curve: compile_error!("exponent must be finite and greater than 0.0")
+                        })
+                    },
+                    "dead_zone" | "sensitivity" => {
+                        let value = float_tokens(lit)?;
+                        Some(quote! { #ident: #value })
+                    },
+                    flag if flags.contains(&flag) => Some(quote! { #ident: #lit }),
+                    _ => None
+                }
+            },
+            _ => None
+        })
+        .collect()
+}
+
+fn dual_axis_tokens(attrs: &syn::MetaList) -> Option<proc_macro2::TokenStream> {
+    let lists = attrs.nested.iter()
+        .map(|nested| match nested {
+            syn::NestedMeta::Meta(syn::Meta::List(list)) => Some(list),
+            _ => None
+        });
+    match attrs.path.get_ident()?.to_string().as_ref() {
+        "Axes" => {
+            let axes = lists.map(|list| axis_tokens(list?)).collect::<Option<Vec<_>>>()?;
+            match axes.as_slice() {
+                [x, y] => Some(quote! { bevy_actions::DualAxis::Axes { x: #x, y: #y } }),
+                _ => None
+            }
+        },
+        "Buttons" => {
+            let buttons = attrs.nested.iter().map(nested_button_tokens).collect::<Option<Vec<_>>>()?;
+            match buttons.as_slice() {
+                [up, down, left, right] => Some(quote! {
+                    bevy_actions::DualAxis::Buttons { up: #up, down: #down, left: #left, right: #right }
+                }),
+                _ => None
+            }
+        },
+        _ => None
+    }
+}
+
+fn nested_button_tokens(nested: &syn::NestedMeta) -> Option<proc_macro2::TokenStream> {
+    match nested {
+        syn::NestedMeta::Meta(syn::Meta::List(button)) => button_tokens(button),
+        syn::NestedMeta::Meta(syn::Meta::Path(path)) => {
+            let ident = path.get_ident()?;
+            ["AnyKey", "AnyMouse", "Any"].contains(&ident.to_string().as_str())
+                .then(|| quote! { bevy_actions::Button::#ident })
+        },
+        _ => None
+    }
+}
+
+fn event_tokens(meta: &syn::MetaList) -> Option<proc_macro2::TokenStream> {
+    let kind = meta.path.get_ident()?;
+    match kind.to_string().as_ref() {
+        "Pressed" | "JustPressed" | "JustReleased" => {
+            let button = nested_button_tokens(meta.nested.first()?)?;
+            Some(quote! { bevy_actions::Event::#kind(#button) })
+        },
+        "Axis" => {
+            let axis = match meta.nested.first()? {
+                syn::NestedMeta::Meta(syn::Meta::List(axis)) => axis_tokens(axis)?,
+                _ => return None
+            };
+            let settings = processing_tokens(meta.nested.iter().skip(1), &["invert", "clamp"], true)?;
+            if settings.is_empty() {
+                Some(quote! { bevy_actions::Event::Axis(#axis) })
+            } else {
+                Some(quote! {
+                    bevy_actions::Event::Processed {
+                        event: Box::new(bevy_actions::Event::Axis(#axis)),
+                        settings: bevy_actions::AxisProcessing { #(#settings,)* ..Default::default() }
+                    }
+                })
+            }
+        },
+        "DualAxis" => {
+            let input = match meta.nested.first()? {
+                syn::NestedMeta::Meta(syn::Meta::List(input)) => dual_axis_tokens(input)?,
+                _ => return None
+            };
+            let settings = processing_tokens(meta.nested.iter().skip(1), &["invert_y", "normalize"], false)?;
+            Some(quote! {
+                bevy_actions::Event::DualAxis {
+                    input: #input,
+                    settings: bevy_actions::DualAxisProcessing { #(#settings,)* ..Default::default() }
+                }
+            })
+        },
+        "Pointer" => match meta.nested.first()? {
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) => {
+                let pointer = path.get_ident()?;
+                Some(quote! { bevy_actions::Event::Pointer(bevy_actions::Pointer::#pointer) })
+            },
+            _ => None
+        },
+        "Drag" => {
+            let mut button = None;
+            let mut threshold = quote! { 0 };
+            let mut pointer = quote! { bevy_actions::Pointer::Cursor };
+            for nested in meta.nested.iter() {
+                match nested {
+                    syn::NestedMeta::Meta(syn::Meta::NameValue(value)) if value.path.is_ident("threshold_px") => {
+                        let lit = &value.lit;
+                        threshold = quote! { #lit };
+                    },
+                    syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("Virtual") => {
+                        pointer = quote! { bevy_actions::Pointer::Virtual };
+                    },
+                    nested => button = Some(nested_button_tokens(nested)?)
+                }
+            }
+            let button = button?;
+            Some(quote! {
+                bevy_actions::Event::Drag { button: #button, pointer: #pointer, threshold_px: #threshold }
+            })
+        },
+        "VirtualAxis" => {
+            let mut buttons = vec![];
+            let mut both = quote! { bevy_actions::BothHeld::Cancel };
+            for nested in meta.nested.iter() {
+                match nested {
+                    syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("last_pressed") => {
+                        both = quote! { bevy_actions::BothHeld::LastPressed };
+                    },
+                    syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("first_pressed") => {
+                        both = quote! { bevy_actions::BothHeld::FirstPressed };
+                    },
+                    nested => buttons.push(nested_button_tokens(nested)?)
+                }
+            }
+            match buttons.as_slice() {
+                [negative, positive] => Some(quote! {
+                    bevy_actions::Event::VirtualAxis { negative: #negative, positive: #positive, both: #both }
+                }),
+                _ => None
+            }
+        },
+        "Chord" => {
+            let buttons = meta.nested.iter()
+                .map(nested_button_tokens)
+                .collect::<Option<Vec<_>>>()?;
+            Some(quote! { bevy_actions::Event::Chord(vec![#(#buttons),*]) })
+        },
+        "DoubleTap" | "LongPress" | "Tap" => {
+            let key = match kind.to_string().as_ref() {
+                "DoubleTap" => "interval_ms",
+                "LongPress" => "hold_ms",
+                _ => "max_ms"
+            };
+            let mut button = None;
+            let mut millis = None;
+            for nested in meta.nested.iter() {
+                match nested {
+                    syn::NestedMeta::Meta(syn::Meta::NameValue(value)) if value.path.is_ident(key) => {
+                        millis = Some(value.lit.clone());
+                    },
+                    nested => button = Some(nested_button_tokens(nested)?)
+                }
+            }
+            let (button, millis) = (button?, millis?);
+            let key = syn::Ident::new(key, Span::call_site());
+            Some(quote! { bevy_actions::Event::#kind { button: #button, #key: #millis } })
+        },
+        "Sequence" => {
+            let mut window = None;
+            let mut events = vec![];
+            for nested in meta.nested.iter() {
+                match nested {
+                    syn::NestedMeta::Meta(syn::Meta::NameValue(value)) if value.path.is_ident("window_ms") => {
+                        window = Some(value.lit.clone());
+                    },
+                    syn::NestedMeta::Meta(syn::Meta::List(event)) => events.push(event_tokens(event)?),
+                    _ => return None
+                }
+            }
+            let window = window?;
+            Some(quote! {
+                bevy_actions::Event::Sequence { events: vec![#(#events),*], window_ms: #window }
+            })
+        },
+        _ => None
+    }
+}
@@ -28,22 +28,22 @@ pub enum SimpleActions {
 impl ConfigActions for SimpleActions {
     const PATH: &'static str = "simple.ron";
 
-    fn default_bindings() -> HashMap<Event, SimpleActions> {
+    fn default_bindings() -> HashMap<SimpleActions, Vec<Event>> {
         let mut map = HashMap::default();
-        map.insert(Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::Q)), SimpleActions::ActionQ);
-        map.insert(Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::W)), SimpleActions::ActionW);
-        map.insert(Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::E)), SimpleActions::ActionE);
-        map.insert(Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::R)), SimpleActions::ActionR);
-        map.insert(Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::T)), SimpleActions::ActionT);
-        map.insert(Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::Y)), SimpleActions::ActionY);
-        map.insert(Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::U)), SimpleActions::ActionU);
-        map.insert(Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::I)), SimpleActions::ActionI);
-        map.insert(Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::O)), SimpleActions::ActionO);
-        map.insert(Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::P)), SimpleActions::ActionP);
-        map.insert(Event::Pressed(bevy_actions::Button::Gamepad(0, GamepadButtonType::South)), SimpleActions::GamepadSouth);
-        map.insert(Event::Axis(bevy_actions::Axis::Mouse(MouseAxis::X)), SimpleActions::MouseX);
-        map.insert(Event::Axis(bevy_actions::Axis::Mouse(MouseAxis::Y)), SimpleActions::MouseY);
-        map.insert(Event::Axis(bevy_actions::Axis::Gamepad(0, GamepadAxisType::LeftStickX)), SimpleActions::LeftStickX);
+        map.insert(SimpleActions::ActionQ, vec![Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::Q))]);
+        map.insert(SimpleActions::ActionW, vec![Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::W))]);
+        map.insert(SimpleActions::ActionE, vec![Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::E))]);
+        map.insert(SimpleActions::ActionR, vec![Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::R))]);
+        map.insert(SimpleActions::ActionT, vec![Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::T))]);
+        map.insert(SimpleActions::ActionY, vec![Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::Y))]);
+        map.insert(SimpleActions::ActionU, vec![Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::U))]);
+        map.insert(SimpleActions::ActionI, vec![Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::I))]);
+        map.insert(SimpleActions::ActionO, vec![Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::O))]);
+        map.insert(SimpleActions::ActionP, vec![Event::Pressed(bevy_actions::Button::Keyboard(KeyCode::P))]);
+        map.insert(SimpleActions::GamepadSouth, vec![Event::Pressed(bevy_actions::Button::Gamepad(0, GamepadButtonType::South))]);
+        map.insert(SimpleActions::MouseX, vec![Event::Axis(bevy_actions::Axis::Mouse(MouseAxis::X))]);
+        map.insert(SimpleActions::MouseY, vec![Event::Axis(bevy_actions::Axis::Mouse(MouseAxis::Y))]);
+        map.insert(SimpleActions::LeftStickX, vec![Event::Axis(bevy_actions::Axis::Gamepad(0, GamepadAxisType::LeftStickX))]);
         map
     }
 }
@@ -1,15 +1,77 @@
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
 use bevy::asset::Handle;
+use bevy::ecs::component::Component;
+use bevy::math::Vec2;
 use bevy::utils::HashMap;
 
-use crate::{ConfigActions, ActionsConfig};
+use crate::{ConfigActions, ActionsConfig, CombinePolicy, Drag, Event, PointerPosition};
+use crate::bindings;
 
-#[derive(Debug)]
-pub struct Actions<T: ConfigActions> {
-    pub handle: Handle<ActionsConfig<T>>,
-    pub data: HashMap<T, Option<f32>>
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
+pub enum ActionPhase {
+    Idle,
+    JustPressed,
+    Held,
+    JustReleased
+}
+
+impl Default for ActionPhase {
+    fn default() -> ActionPhase {
+        ActionPhase::Idle
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct ActionStatus {
+    pub phase: ActionPhase,
+    pub pressed_at: Option<Duration>,
+    pub released_at: Option<Duration>,
+    pub held: Duration
 }
 
-impl <A: ConfigActions>Actions<A> {
+impl ActionStatus {
+    pub fn pressed(&self) -> bool {
+        matches!(self.phase, ActionPhase::JustPressed | ActionPhase::Held)
+    }
+
+    pub(crate) fn press(&mut self, now: Duration) {
+        match self.phase {
+            ActionPhase::Idle | ActionPhase::JustReleased => {
+                self.phase = ActionPhase::JustPressed;
+                self.pressed_at = Some(now);
+                self.held = Duration::ZERO;
+            },
+            ActionPhase::JustPressed | ActionPhase::Held => {
+                self.phase = ActionPhase::Held;
+                self.held = self.pressed_at.map(|at| now.saturating_sub(at)).unwrap_or_default();
+            }
+        }
+    }
+
+    pub(crate) fn release(&mut self, now: Duration) {
+        match self.phase {
+            ActionPhase::JustPressed | ActionPhase::Held => {
+                self.phase = ActionPhase::JustReleased;
+                self.released_at = Some(now);
+                self.held = self.pressed_at.map(|at| now.saturating_sub(at)).unwrap_or_default();
+            },
+            ActionPhase::JustReleased => self.phase = ActionPhase::Idle,
+            ActionPhase::Idle => {}
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct ActionState<T: ConfigActions> {
+    pub data: HashMap<T, Option<f32>>,
+    pub axis_pairs: HashMap<T, Vec2>,
+    pub pointers: HashMap<T, PointerPosition>,
+    pub drags: HashMap<T, Drag>,
+    pub states: HashMap<T, ActionStatus>
+}
+
+impl <A: ConfigActions>ActionState<A> {
     pub fn action(&self, action: A) -> bool {
         self.data.contains_key(&action)
     }
@@ -17,13 +79,248 @@ impl <A: ConfigActions>Actions<A> {
     pub fn value(&self, action: A) -> Option<f32> {
         self.data.get(&action).map(|x|x.map(|x|x)).flatten()
     }
+
+    pub fn axis_pair(&self, action: A) -> Option<Vec2> {
+        self.axis_pairs.get(&action).copied()
+    }
+
+    pub fn pointer(&self, action: A) -> Option<PointerPosition> {
+        self.pointers.get(&action).copied()
+    }
+
+    pub fn drag(&self, action: A) -> Option<Drag> {
+        self.drags.get(&action).copied()
+    }
+
+    pub fn status(&self, action: A) -> ActionStatus {
+        self.states.get(&action).copied().unwrap_or_default()
+    }
+
+    pub fn phase(&self, action: A) -> ActionPhase {
+        self.status(action).phase
+    }
+
+    pub fn pressed(&self, action: A) -> bool {
+        self.status(action).pressed()
+    }
+
+    pub fn just_pressed(&self, action: A) -> bool {
+        self.phase(action) == ActionPhase::JustPressed
+    }
+
+    pub fn just_released(&self, action: A) -> bool {
+        self.phase(action) == ActionPhase::JustReleased
+    }
+
+    pub fn pressed_at(&self, action: A) -> Option<Duration> {
+        let status = self.status(action);
+        (status.phase != ActionPhase::Idle).then(|| status.pressed_at).flatten()
+    }
+
+    pub fn held_for(&self, action: A) -> Option<Duration> {
+        let status = self.status(action);
+        (status.phase != ActionPhase::Idle).then(|| status.held)
+    }
+
+    pub(crate) fn update(&mut self, now: Duration) {
+        let ActionState { data, states, .. } = self;
+        for (action, status) in states.iter_mut() {
+            if !data.contains_key(action) {
+                status.release(now);
+            }
+        }
+        for action in data.keys() {
+            states.entry(*action).or_default().press(now);
+        }
+    }
+}
+
+impl <T: ConfigActions>Default for ActionState<T> {
+    fn default() -> ActionState<T> {
+        ActionState {
+            data: Default::default(),
+            axis_pairs: Default::default(),
+            pointers: Default::default(),
+            drags: Default::default(),
+            states: Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct InputMap<T: ConfigActions> {
+    pub data: HashMap<Event, Vec<T>>,
+    pub combine: HashMap<T, CombinePolicy>,
+    pub player: usize
+}
+
+impl <A: ConfigActions>InputMap<A> {
+    pub fn for_player(player: usize) -> InputMap<A> {
+        InputMap { player, ..Default::default() }
+    }
+
+    pub fn actions(&self, event: &Event) -> &[A] {
+        bindings::actions(&self.data, event)
+    }
+
+    pub fn events(&self, action: A) -> Vec<&Event> {
+        bindings::events(&self.data, action)
+    }
+
+    pub fn bind(&mut self, event: Event, action: A) -> &mut Self {
+        bindings::insert(&mut self.data, event, action);
+        self
+    }
+
+    pub fn unbind(&mut self, event: &Event, action: A) -> bool {
+        bindings::remove(&mut self.data, event, action)
+    }
+
+    pub fn combine_policy(&self, action: A) -> CombinePolicy {
+        self.combine.get(&action).copied().unwrap_or_default()
+    }
+
+    pub fn set_combine_policy(&mut self, action: A, policy: CombinePolicy) -> &mut Self {
+        self.combine.insert(action, policy);
+        self
+    }
+}
+
+impl <T: ConfigActions>Default for InputMap<T> {
+    fn default() -> InputMap<T> {
+        InputMap {
+            data: Default::default(),
+            combine: Default::default(),
+            player: 0
+        }
+    }
+}
+
+impl <A: ConfigActions>From<HashMap<A, Vec<Event>>> for InputMap<A> {
+    fn from(bindings: HashMap<A, Vec<Event>>) -> InputMap<A> {
+        let mut map = InputMap::default();
+        for (action, events) in bindings.into_iter() {
+            for event in events.into_iter() {
+                map.bind(event, action);
+            }
+        }
+        map
+    }
+}
+
+impl <A: ConfigActions>From<&ActionsConfig<A>> for InputMap<A> {
+    fn from(config: &ActionsConfig<A>) -> InputMap<A> {
+        InputMap {
+            data: config.data.clone(),
+            combine: config.combine.clone(),
+            player: 0
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Actions<T: ConfigActions> {
+    pub handle: Handle<ActionsConfig<T>>,
+    pub state: ActionState<T>
+}
+
+impl <T: ConfigActions>Deref for Actions<T> {
+    type Target = ActionState<T>;
+
+    fn deref(&self) -> &ActionState<T> {
+        &self.state
+    }
+}
+
+impl <T: ConfigActions>DerefMut for Actions<T> {
+    fn deref_mut(&mut self) -> &mut ActionState<T> {
+        &mut self.state
+    }
 }
 
 impl <T: ConfigActions>Default for Actions<T> {
     fn default() -> Actions<T> {
         Actions {
             handle: Default::default(),
-            data: Default::default()
+            state: Default::default()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestActions;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn status_goes_through_phases() {
+        let mut status = ActionStatus::default();
+        assert_eq!(status.phase, ActionPhase::Idle);
+
+        status.press(ms(100));
+        assert_eq!(status.phase, ActionPhase::JustPressed);
+        assert_eq!(status.pressed_at, Some(ms(100)));
+        assert_eq!(status.held, Duration::ZERO);
+        assert!(status.pressed());
+
+        status.press(ms(150));
+        assert_eq!(status.phase, ActionPhase::Held);
+        assert_eq!(status.held, ms(50));
+
+        status.press(ms(300));
+        assert_eq!(status.phase, ActionPhase::Held);
+        assert_eq!(status.pressed_at, Some(ms(100)));
+        assert_eq!(status.held, ms(200));
+
+        status.release(ms(320));
+        assert_eq!(status.phase, ActionPhase::JustReleased);
+        assert_eq!(status.released_at, Some(ms(320)));
+        assert_eq!(status.held, ms(220));
+        assert!(!status.pressed());
+
+        status.release(ms(340));
+        assert_eq!(status.phase, ActionPhase::Idle);
+        status.release(ms(360));
+        assert_eq!(status.phase, ActionPhase::Idle);
+        assert_eq!(status.released_at, Some(ms(320)));
+    }
+
+    #[test]
+    fn press_after_release_starts_over() {
+        let mut status = ActionStatus::default();
+        status.press(ms(0));
+        status.release(ms(50));
+        status.press(ms(60));
+        assert_eq!(status.phase, ActionPhase::JustPressed);
+        assert_eq!(status.pressed_at, Some(ms(60)));
+        assert_eq!(status.held, Duration::ZERO);
+    }
+
+    #[test]
+    fn state_updates_from_data() {
+        let mut state = ActionState::<TestActions>::default();
+        state.data.insert(TestActions::Jump, None);
+        state.update(ms(0));
+        assert!(state.just_pressed(TestActions::Jump));
+        assert_eq!(state.pressed_at(TestActions::Jump), Some(ms(0)));
+        assert_eq!(state.phase(TestActions::Fire), ActionPhase::Idle);
+
+        state.update(ms(100));
+        assert_eq!(state.phase(TestActions::Jump), ActionPhase::Held);
+        assert_eq!(state.held_for(TestActions::Jump), Some(ms(100)));
+
+        state.data.clear();
+        state.update(ms(120));
+        assert!(state.just_released(TestActions::Jump));
+        assert_eq!(state.held_for(TestActions::Jump), Some(ms(120)));
+
+        state.update(ms(140));
+        assert_eq!(state.phase(TestActions::Jump), ActionPhase::Idle);
+        assert_eq!(state.pressed_at(TestActions::Jump), None);
+        assert_eq!(state.held_for(TestActions::Jump), None);
+    }
+}
@@ -0,0 +1,44 @@
+use bevy::utils::HashMap;
+
+use crate::Event;
+
+pub(crate) type Bindings<A> = HashMap<Event, Vec<A>>;
+
+pub(crate) fn actions<'a, A>(data: &'a Bindings<A>, event: &Event) -> &'a [A] {
+    data.get(event).map(|x|x.as_slice()).unwrap_or(&[])
+}
+
+pub(crate) fn events<A: PartialEq>(data: &Bindings<A>, action: A) -> Vec<&Event> {
+    data.iter()
+        .filter_map(|(event, x)| x.contains(&action).then(|| event))
+        .collect()
+}
+
+pub(crate) fn insert<A: PartialEq>(data: &mut Bindings<A>, event: Event, action: A) {
+    let actions = data.entry(event).or_default();
+    if !actions.contains(&action) {
+        actions.push(action);
+    }
+}
+
+pub(crate) fn remove<A: PartialEq>(data: &mut Bindings<A>, event: &Event, action: A) -> bool {
+    let removed = match data.get_mut(event) {
+        Some(actions) => {
+            let len = actions.len();
+            actions.retain(|x| *x != action);
+            actions.len() != len
+        },
+        None => false
+    };
+    if data.get(event).map(|x|x.is_empty()).unwrap_or(false) {
+        data.remove(event);
+    }
+    removed
+}
+
+pub(crate) fn remove_action<A: PartialEq>(data: &mut Bindings<A>, action: A) {
+    data.retain(|_, actions| {
+        actions.retain(|x| *x != action);
+        !actions.is_empty()
+    });
+}
@@ -1,27 +1,363 @@
+use bevy::asset::Handle;
+use bevy::math::Vec2;
 use bevy::reflect::TypeUuid;
-use bevy::utils::{HashMap, Uuid};
+use bevy::utils::{HashMap, HashSet, Uuid};
+use serde::{ Serialize, Deserialize };
 
-use crate::{ConfigActions, Event};
+use crate::{ConfigActions, ConfigLayer, Event, LayerBindings};
+use crate::bindings::{self, Bindings};
+
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, Serialize, Deserialize)]
+pub enum CombinePolicy {
+    MaxMagnitude,
+    Sum
+}
+
+impl Default for CombinePolicy {
+    fn default() -> CombinePolicy {
+        CombinePolicy::MaxMagnitude
+    }
+}
+
+impl CombinePolicy {
+    pub fn combine(&self, current: f32, value: f32) -> f32 {
+        match self {
+            CombinePolicy::MaxMagnitude => if value.abs() > current.abs() { value } else { current },
+            CombinePolicy::Sum => current + value
+        }
+    }
+
+    pub fn combine_pair(&self, current: Vec2, value: Vec2) -> Vec2 {
+        match self {
+            CombinePolicy::MaxMagnitude => if value.length() > current.length() { value } else { current },
+            CombinePolicy::Sum => current + value
+        }
+    }
+}
+
+type Sources<A> = HashMap<(Event, A), ConfigLayer>;
 
 #[derive(Debug)]
 pub struct ActionsConfig<A: ConfigActions> {
-    pub data: HashMap<Event, A>
+    pub data: HashMap<Event, Vec<A>>,
+    pub sources: HashMap<(Event, A), ConfigLayer>,
+    pub combine: HashMap<A, CombinePolicy>,
+    pub layers: Vec<(ConfigLayer, LayerBindings<A>)>
 }
 
 impl <A: ConfigActions>Default for ActionsConfig<A> {
     fn default() -> ActionsConfig<A> {
-        ActionsConfig {
-            data: A::default_bindings()
-        }
+        let mut config = ActionsConfig {
+            data: Default::default(),
+            sources: Default::default(),
+            combine: Default::default(),
+            layers: vec![(ConfigLayer::Defaults, LayerBindings::added(A::default_bindings()))]
+        };
+        config.resolve();
+        config
     }
 }
 
 impl <A: ConfigActions>ActionsConfig<A> {
     pub fn action(&self, event: Event) -> Option<A> {
-        self.data.get(&event).map(|x|(*x))
+        self.data.get(&event).and_then(|x|x.first().copied())
+    }
+
+    pub fn actions(&self, event: &Event) -> &[A] {
+        bindings::actions(&self.data, event)
+    }
+
+    pub fn events(&self, action: A) -> Vec<&Event> {
+        bindings::events(&self.data, action)
+    }
+
+    pub fn conflicts(&self, event: &Event, action: A) -> Vec<A> {
+        self.actions(event).iter()
+            .copied()
+            .filter(|x| *x != action)
+            .collect()
+    }
+
+    pub fn combine_policy(&self, action: A) -> CombinePolicy {
+        self.combine.get(&action).copied().unwrap_or_default()
+    }
+
+    pub fn set_combine_policy(&mut self, action: A, policy: CombinePolicy) {
+        self.combine.insert(action, policy);
+    }
+
+    pub fn source(&self, event: &Event, action: A) -> Option<ConfigLayer> {
+        self.sources.get(&(event.clone(), action)).copied()
+    }
+
+    pub fn bind(&mut self, event: Event, action: A) {
+        Self::insert(&mut self.data, &mut self.sources, event, action, ConfigLayer::User);
+    }
+
+    pub fn unbind(&mut self, event: &Event, action: A) -> bool {
+        Self::remove(&mut self.data, &mut self.sources, event, action)
+    }
+
+    pub fn unbind_event(&mut self, event: &Event) -> Vec<A> {
+        let actions = self.data.remove(event).unwrap_or_default();
+        for action in actions.iter() {
+            self.sources.remove(&(event.clone(), *action));
+        }
+        actions
+    }
+
+    pub fn unbind_action(&mut self, action: A) {
+        Self::remove_action(&mut self.data, &mut self.sources, action);
+    }
+
+    pub fn push_layer(&mut self, layer: ConfigLayer, bindings: LayerBindings<A>) {
+        self.layers.push((layer, bindings));
+        self.layers.sort_by_key(|(layer, _)| *layer);
+        self.resolve();
+    }
+
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        self.layers.iter().try_for_each(|(layer, bindings)| {
+            bindings.validate().map_err(|err| anyhow::anyhow!("Invalid {:?} bindings: {}", layer, err))
+        })
+    }
+
+    pub fn resolve(&mut self) {
+        let (data, sources) = Self::resolve_layers(self.layers.iter());
+        self.data = data;
+        self.sources = sources;
+        self.combine = Self::resolve_combine(self.layers.iter());
+    }
+
+    fn insert(data: &mut Bindings<A>, sources: &mut Sources<A>, event: Event, action: A, layer: ConfigLayer) {
+        bindings::insert(data, event.clone(), action);
+        sources.insert((event, action), layer);
+    }
+
+    fn remove(data: &mut Bindings<A>, sources: &mut Sources<A>, event: &Event, action: A) -> bool {
+        sources.remove(&(event.clone(), action));
+        bindings::remove(data, event, action)
+    }
+
+    fn remove_action(data: &mut Bindings<A>, sources: &mut Sources<A>, action: A) {
+        bindings::remove_action(data, action);
+        sources.retain(|(_, x), _| *x != action);
+    }
+
+    fn resolve_layers<'a>(
+        layers: impl Iterator<Item = &'a (ConfigLayer, LayerBindings<A>)>
+    ) -> (Bindings<A>, Sources<A>) {
+        let mut data: Bindings<A> = HashMap::default();
+        let mut sources: Sources<A> = HashMap::default();
+        for (layer, bindings) in layers {
+            for (action, events) in bindings.replace.iter() {
+                Self::remove_action(&mut data, &mut sources, *action);
+                for event in events.iter() {
+                    Self::insert(&mut data, &mut sources, event.clone(), *action, *layer);
+                }
+            }
+            for (action, events) in bindings.unbind.iter() {
+                for event in events.iter() {
+                    Self::remove(&mut data, &mut sources, event, *action);
+                }
+            }
+            for (action, events) in bindings.add.iter() {
+                for event in events.iter() {
+                    Self::insert(&mut data, &mut sources, event.clone(), *action, *layer);
+                }
+            }
+        }
+        (data, sources)
+    }
+
+    fn resolve_combine<'a>(
+        layers: impl Iterator<Item = &'a (ConfigLayer, LayerBindings<A>)>
+    ) -> HashMap<A, CombinePolicy> {
+        layers
+            .flat_map(|(_, bindings)| bindings.combine.iter().map(|(action, policy)| (*action, *policy)))
+            .collect()
+    }
+
+    fn group(data: &Bindings<A>) -> HashMap<A, HashSet<&Event>> {
+        let mut output: HashMap<A, HashSet<&Event>> = HashMap::default();
+        for (event, actions) in data.iter() {
+            for action in actions.iter() {
+                output.entry(*action).or_default().insert(event);
+            }
+        }
+        output
+    }
+
+    pub fn bindings(&self) -> HashMap<A, Vec<Event>> {
+        Self::group(&self.data).into_iter()
+            .map(|(action, events)| (action, events.into_iter().cloned().collect()))
+            .collect()
+    }
+
+    pub fn overrides(&self) -> HashMap<A, Vec<Event>> {
+        let (defaults, _) = Self::resolve_layers(
+            self.layers.iter().filter(|(layer, _)| *layer < ConfigLayer::User)
+        );
+        let current = Self::group(&self.data);
+        let defaults = Self::group(&defaults);
+        let empty = HashSet::default();
+        let actions: HashSet<A> = current.keys().chain(defaults.keys()).copied().collect();
+        let mut output = HashMap::default();
+        for action in actions.into_iter() {
+            let events = current.get(&action).unwrap_or(&empty);
+            if events != defaults.get(&action).unwrap_or(&empty) {
+                output.insert(action, events.iter().map(|event| (*event).clone()).collect());
+            }
+        }
+        output
+    }
+
+    pub fn combine_overrides(&self) -> HashMap<A, CombinePolicy> {
+        let defaults = Self::resolve_combine(
+            self.layers.iter().filter(|(layer, _)| *layer < ConfigLayer::User)
+        );
+        self.combine.iter()
+            .filter(|(action, policy)| defaults.get(action).copied().unwrap_or_default() != **policy)
+            .map(|(action, policy)| (*action, *policy))
+            .collect()
     }
 }
 
 impl <A: ConfigActions>TypeUuid for ActionsConfig<A> {
     const TYPE_UUID: Uuid = A::TYPE_UUID;
 }
+
+#[derive(Debug)]
+pub struct ActionsConfigChanged<A: ConfigActions> {
+    pub handle: Handle<ActionsConfig<A>>
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::keyboard::KeyCode;
+    use super::*;
+    use crate::Button;
+    use crate::tests::TestActions;
+
+    fn key(key: KeyCode) -> Event {
+        Event::Pressed(Button::Keyboard(key))
+    }
+
+    fn bindings(action: TestActions, keys: &[KeyCode]) -> HashMap<TestActions, Vec<Event>> {
+        [(action, keys.iter().map(|x| key(*x)).collect::<Vec<Event>>())].into_iter().collect()
+    }
+
+    fn events(config: &ActionsConfig<TestActions>, action: TestActions) -> HashSet<Event> {
+        config.events(action).into_iter().cloned().collect()
+    }
+
+    fn set(keys: &[KeyCode]) -> HashSet<Event> {
+        keys.iter().map(|x| key(*x)).collect()
+    }
+
+    #[test]
+    fn layer_applies_replace_then_unbind_then_add() {
+        let mut config = ActionsConfig::<TestActions>::default();
+        config.push_layer(ConfigLayer::Asset, LayerBindings::added(bindings(TestActions::Jump, &[KeyCode::Space])));
+        config.push_layer(ConfigLayer::User, LayerBindings {
+            replace: bindings(TestActions::Jump, &[KeyCode::W, KeyCode::Up]),
+            unbind: bindings(TestActions::Jump, &[KeyCode::W, KeyCode::Up]),
+            add: bindings(TestActions::Jump, &[KeyCode::Up]),
+            ..Default::default()
+        });
+        assert_eq!(events(&config, TestActions::Jump), set(&[KeyCode::Up]));
+        assert_eq!(config.source(&key(KeyCode::Up), TestActions::Jump), Some(ConfigLayer::User));
+    }
+
+    #[test]
+    fn layers_apply_in_order() {
+        let mut config = ActionsConfig::<TestActions>::default();
+        config.push_layer(ConfigLayer::User, LayerBindings::replaced(bindings(TestActions::Jump, &[KeyCode::Up])));
+        config.push_layer(ConfigLayer::Platform, LayerBindings {
+            unbind: bindings(TestActions::Fire, &[KeyCode::F]),
+            ..Default::default()
+        });
+        config.push_layer(ConfigLayer::Asset, LayerBindings {
+            add: [
+                (TestActions::Jump, vec![key(KeyCode::Space)]),
+                (TestActions::Fire, vec![key(KeyCode::F), key(KeyCode::G)])
+            ].into_iter().collect(),
+            ..Default::default()
+        });
+        assert_eq!(events(&config, TestActions::Jump), set(&[KeyCode::Up]));
+        assert_eq!(events(&config, TestActions::Fire), set(&[KeyCode::G]));
+        assert_eq!(config.source(&key(KeyCode::G), TestActions::Fire), Some(ConfigLayer::Asset));
+        assert_eq!(config.source(&key(KeyCode::F), TestActions::Fire), None);
+    }
+
+    #[test]
+    fn overrides_only_lists_changed_actions() {
+        let mut config = ActionsConfig::<TestActions>::default();
+        config.push_layer(ConfigLayer::Asset, LayerBindings {
+            add: [
+                (TestActions::Jump, vec![key(KeyCode::Space)]),
+                (TestActions::Fire, vec![key(KeyCode::F)]),
+                (TestActions::Confirm, vec![key(KeyCode::Return)])
+            ].into_iter().collect(),
+            ..Default::default()
+        });
+        assert!(config.overrides().is_empty());
+
+        config.bind(key(KeyCode::Up), TestActions::Jump);
+        config.unbind_action(TestActions::Fire);
+        config.unbind(&key(KeyCode::Return), TestActions::Confirm);
+        config.bind(key(KeyCode::Return), TestActions::Confirm);
+        let overrides = config.overrides();
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(
+            overrides[&TestActions::Jump].iter().cloned().collect::<HashSet<Event>>(),
+            set(&[KeyCode::Space, KeyCode::Up])
+        );
+        assert!(overrides[&TestActions::Fire].is_empty());
+    }
+
+    #[test]
+    fn combine_overrides_only_lists_changed_policies() {
+        let mut config = ActionsConfig::<TestActions>::default();
+        config.push_layer(ConfigLayer::Asset, LayerBindings {
+            combine: [(TestActions::Fire, CombinePolicy::Sum)].into_iter().collect(),
+            ..Default::default()
+        });
+        assert!(config.combine_overrides().is_empty());
+
+        config.set_combine_policy(TestActions::Jump, CombinePolicy::MaxMagnitude);
+        assert!(config.combine_overrides().is_empty());
+        config.set_combine_policy(TestActions::Jump, CombinePolicy::Sum);
+        config.set_combine_policy(TestActions::Fire, CombinePolicy::MaxMagnitude);
+        let overrides = config.combine_overrides();
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(overrides[&TestActions::Jump], CombinePolicy::Sum);
+        assert_eq!(overrides[&TestActions::Fire], CombinePolicy::MaxMagnitude);
+    }
+
+    #[test]
+    fn invalid_default_layer_fails_validation() {
+        let mut config = ActionsConfig::<TestActions>::default();
+        assert!(config.validate().is_ok());
+        let event = Event::Processed {
+            event: Box::new(key(KeyCode::Space)),
+            settings: crate::AxisProcessing { dead_zone: 1.5, ..Default::default() }
+        };
+        config.push_layer(ConfigLayer::Defaults, LayerBindings::added([(TestActions::Jump, vec![event])].into_iter().collect()));
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn user_overrides_survive_reload() {
+        let mut config = ActionsConfig::<TestActions>::default();
+        config.push_layer(ConfigLayer::Asset, LayerBindings::added(bindings(TestActions::Jump, &[KeyCode::Space])));
+        config.bind(key(KeyCode::Up), TestActions::Jump);
+        config.unbind(&key(KeyCode::Space), TestActions::Jump);
+
+        let mut reloaded = ActionsConfig::<TestActions>::default();
+        reloaded.push_layer(ConfigLayer::Asset, LayerBindings::added(bindings(TestActions::Jump, &[KeyCode::Space])));
+        reloaded.push_layer(ConfigLayer::User, LayerBindings::replaced(config.overrides()));
+        assert_eq!(events(&reloaded, TestActions::Jump), set(&[KeyCode::Up]));
+        assert!(reloaded.overrides().contains_key(&TestActions::Jump));
+    }
+}
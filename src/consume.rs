@@ -0,0 +1,40 @@
+use bevy::utils::HashSet;
+
+use crate::{Axis, Button};
+
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
+pub enum Device {
+    Keyboard,
+    Mouse,
+    Gamepad
+}
+
+#[derive(Debug, Default)]
+pub struct ConsumedInput {
+    pub buttons: HashSet<Button>,
+    pub devices: HashSet<Device>
+}
+
+impl ConsumedInput {
+    pub fn consume(&mut self, button: Button) {
+        self.buttons.insert(button);
+    }
+
+    pub fn consume_device(&mut self, device: Device) {
+        self.devices.insert(device);
+    }
+
+    pub fn is_consumed(&self, button: &Button) -> bool {
+        button.device().map(|device| self.devices.contains(&device)).unwrap_or(false)
+            || self.buttons.contains(button)
+    }
+
+    pub fn is_axis_consumed(&self, axis: &Axis) -> bool {
+        self.devices.contains(&axis.device())
+    }
+
+    pub fn clear(&mut self) {
+        self.buttons.clear();
+        self.devices.clear();
+    }
+}
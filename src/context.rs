@@ -0,0 +1,96 @@
+use bevy::ecs::component::Component;
+use bevy::utils::HashSet;
+
+use crate::ConfigActions;
+
+#[derive(Debug, Clone)]
+pub struct ActionContext<A: ConfigActions> {
+    pub name: String,
+    pub priority: i32,
+    pub enabled: bool,
+    pub actions: HashSet<A>
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct ActionContexts<A: ConfigActions> {
+    pub contexts: Vec<ActionContext<A>>,
+    pub stack: Vec<String>
+}
+
+impl <A: ConfigActions>Default for ActionContexts<A> {
+    fn default() -> ActionContexts<A> {
+        ActionContexts {
+            contexts: Default::default(),
+            stack: Default::default()
+        }
+    }
+}
+
+impl <A: ConfigActions>ActionContexts<A> {
+    pub fn add(&mut self, name: impl Into<String>, priority: i32, actions: impl IntoIterator<Item = A>) -> &mut Self {
+        let name = name.into();
+        self.contexts.retain(|context| context.name != name);
+        self.contexts.push(ActionContext {
+            name,
+            priority,
+            enabled: true,
+            actions: actions.into_iter().collect()
+        });
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ActionContext<A>> {
+        self.contexts.iter().find(|context| context.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut ActionContext<A>> {
+        self.contexts.iter_mut().find(|context| context.name == name)
+    }
+
+    pub fn enabled(&self, name: &str) -> bool {
+        self.get(name).map(|context| context.enabled).unwrap_or(false)
+    }
+
+    pub fn enable(&mut self, name: &str) {
+        if let Some(context) = self.get_mut(name) {
+            context.enabled = true;
+        }
+    }
+
+    pub fn disable(&mut self, name: &str) {
+        if let Some(context) = self.get_mut(name) {
+            context.enabled = false;
+        }
+    }
+
+    pub fn set_priority(&mut self, name: &str, priority: i32) {
+        if let Some(context) = self.get_mut(name) {
+            context.priority = priority;
+        }
+    }
+
+    pub fn push(&mut self, name: &str) {
+        self.enable(name);
+        self.stack.push(name.to_string());
+    }
+
+    pub fn pop(&mut self) -> Option<String> {
+        let name = self.stack.pop()?;
+        if !self.stack.contains(&name) {
+            self.disable(&name);
+        }
+        Some(name)
+    }
+
+    pub fn priority(&self, action: A) -> Option<i32> {
+        let mut found = false;
+        let mut priority = None;
+        for context in self.contexts.iter().filter(|context| context.actions.contains(&action)) {
+            found = true;
+            if context.enabled {
+                priority = priority.max(Some(context.priority));
+            }
+        }
+        if found { priority } else { Some(i32::MIN) }
+    }
+}
@@ -3,29 +3,197 @@ use bevy::input::mouse::MouseButton;
 use serde::{ Serialize, Deserialize };
 use bevy::input::gamepad::{GamepadButtonType, GamepadAxisType};
 
+use crate::{AxisProcessing, DualAxisProcessing, Device, Pointer, WheelDirection};
+
 #[derive(Debug, PartialEq, Clone, Eq, Hash, Serialize, Deserialize)]
 pub enum Event {
     Pressed(Button),
     JustPressed(Button),
+    JustReleased(Button),
     Value(Button),
     Axis(Axis),
+    Chord(Vec<Button>),
+    Sequence { events: Vec<Event>, window_ms: u64 },
+    DoubleTap { button: Button, interval_ms: u64 },
+    LongPress { button: Button, hold_ms: u64 },
+    Tap { button: Button, max_ms: u64 },
+    Processed { event: Box<Event>, settings: AxisProcessing },
+    DualAxis {
+        input: DualAxis,
+        #[serde(default)]
+        settings: DualAxisProcessing
+    },
+    VirtualAxis {
+        negative: Button,
+        positive: Button,
+        #[serde(default)]
+        both: BothHeld
+    },
+    Pointer(Pointer),
+    Drag {
+        button: Button,
+        #[serde(default)]
+        pointer: Pointer,
+        #[serde(default)]
+        threshold_px: u32
+    },
+}
+
+impl Event {
+    pub fn is_analog(&self) -> bool {
+        match self {
+            Event::Processed { event, .. } => event.is_analog(),
+            _ => matches!(self, Event::Value(_) | Event::Axis(_) | Event::DualAxis { .. } | Event::VirtualAxis { .. })
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        match self {
+            Event::Sequence { events, .. } => events.iter().try_for_each(Event::validate),
+            Event::Processed { event, settings } => {
+                settings.validate()?;
+                event.validate()
+            },
+            Event::DualAxis { settings, .. } => settings.validate(),
+            _ => Ok(())
+        }
+    }
+
+    pub fn buttons(&self) -> Vec<&Button> {
+        match self {
+            Event::Chord(buttons) => buttons.iter().collect(),
+            Event::Sequence { events, .. } => events.iter().flat_map(|event| event.buttons()).collect(),
+            Event::Processed { event, .. } => event.buttons(),
+            Event::DualAxis { input: DualAxis::Buttons { up, down, left, right }, .. } => {
+                vec![up, down, left, right]
+            },
+            Event::VirtualAxis { negative, positive, .. } => vec![negative, positive],
+            _ => self.button().into_iter().collect()
+        }
+    }
+
+    pub fn axes(&self) -> Vec<&Axis> {
+        match self {
+            Event::Axis(axis) => vec![axis],
+            Event::Sequence { events, .. } => events.iter().flat_map(|event| event.axes()).collect(),
+            Event::Processed { event, .. } => event.axes(),
+            Event::DualAxis { input: DualAxis::Axes { x, y }, .. } => vec![x, y],
+            _ => vec![]
+        }
+    }
+
+    pub fn button(&self) -> Option<&Button> {
+        match self {
+            Event::Pressed(button) | Event::JustPressed(button) |
+            Event::JustReleased(button) | Event::Value(button) |
+            Event::DoubleTap { button, .. } | Event::LongPress { button, .. } |
+            Event::Tap { button, .. } | Event::Drag { button, .. } => Some(button),
+            Event::Processed { event, .. } => event.button(),
+            _ => None
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Eq, Hash, Serialize, Deserialize)]
 pub enum Button {
     Keyboard(KeyCode),
     Mouse(MouseButton),
-    Gamepad(usize, GamepadButtonType)
+    Gamepad(usize, GamepadButtonType),
+    PlayerGamepad(GamepadButtonType),
+    AnyGamepad(GamepadButtonType),
+    Wheel(WheelDirection),
+    AnyKey,
+    AnyMouse,
+    Any
+}
+
+impl Button {
+    pub fn matches(&self, other: &Button) -> bool {
+        match (self, other) {
+            (Button::AnyGamepad(x), Button::Gamepad(_, y)) => x == y,
+            (Button::AnyKey, Button::Keyboard(_)) => true,
+            (Button::AnyMouse, Button::Mouse(_)) => true,
+            (Button::Any, Button::Keyboard(_) | Button::Mouse(_) | Button::Gamepad(..)) => true,
+            _ => self == other
+        }
+    }
+
+    pub fn device(&self) -> Option<Device> {
+        match self {
+            Button::Keyboard(_) | Button::AnyKey => Some(Device::Keyboard),
+            Button::Mouse(_) | Button::AnyMouse | Button::Wheel(_) => Some(Device::Mouse),
+            Button::Gamepad(..) | Button::PlayerGamepad(_) |
+            Button::AnyGamepad(_) => Some(Device::Gamepad),
+            Button::Any => None
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Eq, Hash, Serialize, Deserialize)]
 pub enum Axis {
     Mouse(MouseAxis),
-    Gamepad(usize, GamepadAxisType)
+    Gamepad(usize, GamepadAxisType),
+    PlayerGamepad(GamepadAxisType),
+    AnyGamepad(GamepadAxisType)
+}
+
+impl Axis {
+    pub fn device(&self) -> Device {
+        match self {
+            Axis::Mouse(_) => Device::Mouse,
+            Axis::Gamepad(..) | Axis::PlayerGamepad(_) |
+            Axis::AnyGamepad(_) => Device::Gamepad
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, Serialize, Deserialize)]
+pub enum BothHeld {
+    Cancel,
+    LastPressed,
+    FirstPressed
+}
+
+impl Default for BothHeld {
+    fn default() -> BothHeld {
+        BothHeld::Cancel
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Eq, Hash, Serialize, Deserialize)]
+pub enum DualAxis {
+    Axes { x: Axis, y: Axis },
+    Buttons { up: Button, down: Button, left: Button, right: Button }
 }
 
 #[derive(Debug, PartialEq, Clone, Eq, Hash, Serialize, Deserialize)]
 pub enum MouseAxis {
     X,
-    Y
+    Y,
+    ScrollX,
+    ScrollY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards_match_only_their_device() {
+        let key = Button::Keyboard(KeyCode::Space);
+        let mouse = Button::Mouse(MouseButton::Left);
+        let pad = Button::Gamepad(1, GamepadButtonType::South);
+        let wheel = Button::Wheel(WheelDirection::Up);
+
+        assert!(Button::AnyKey.matches(&key));
+        assert!(!Button::AnyKey.matches(&mouse) && !Button::AnyKey.matches(&pad) && !Button::AnyKey.matches(&wheel));
+        assert!(Button::AnyMouse.matches(&mouse));
+        assert!(!Button::AnyMouse.matches(&key) && !Button::AnyMouse.matches(&pad) && !Button::AnyMouse.matches(&wheel));
+        assert!(Button::AnyGamepad(GamepadButtonType::South).matches(&pad));
+        assert!(!Button::AnyGamepad(GamepadButtonType::East).matches(&pad));
+        assert!(!Button::AnyGamepad(GamepadButtonType::South).matches(&key));
+        assert!(Button::Any.matches(&key) && Button::Any.matches(&mouse) && Button::Any.matches(&pad));
+        assert!(!Button::Any.matches(&wheel));
+        assert!(!key.matches(&Button::AnyKey));
+    }
 }
@@ -0,0 +1,105 @@
+use bevy::input::gamepad::GamepadButtonType;
+use bevy::utils::HashMap;
+
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
+pub enum GamepadAssignmentEvent {
+    Joined { player: usize, gamepad: usize },
+    Disconnected { player: usize, gamepad: usize },
+    Reconnected { player: usize, gamepad: usize }
+}
+
+#[derive(Debug)]
+pub struct GamepadAssignments {
+    pub players: HashMap<usize, usize>,
+    pub connected: Vec<usize>,
+    pub max_players: usize,
+    pub join: Option<GamepadButtonType>
+}
+
+impl Default for GamepadAssignments {
+    fn default() -> GamepadAssignments {
+        GamepadAssignments {
+            players: Default::default(),
+            connected: Default::default(),
+            max_players: 4,
+            join: None
+        }
+    }
+}
+
+impl GamepadAssignments {
+    pub fn gamepad(&self, player: usize) -> Option<usize> {
+        self.players.get(&player).copied()
+    }
+
+    pub fn player(&self, gamepad: usize) -> Option<usize> {
+        self.players.iter()
+            .find_map(|(player, x)| (*x == gamepad).then(|| *player))
+    }
+
+    pub fn connected(&self, gamepad: usize) -> bool {
+        self.connected.contains(&gamepad)
+    }
+
+    pub fn assign(&mut self, player: usize, gamepad: usize) {
+        self.players.retain(|_, x| *x != gamepad);
+        self.players.insert(player, gamepad);
+    }
+
+    pub fn unassign(&mut self, player: usize) -> Option<usize> {
+        self.players.remove(&player)
+    }
+
+    pub fn free_player(&self) -> Option<usize> {
+        (0..self.max_players).find(|player| !self.players.contains_key(player))
+    }
+
+    pub(crate) fn join(&mut self, gamepad: usize) -> Option<usize> {
+        if self.player(gamepad).is_some() {
+            return None;
+        }
+        let player = self.free_player()?;
+        self.players.insert(player, gamepad);
+        Some(player)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assigned_gamepad_does_not_join_twice() {
+        let mut assignments = GamepadAssignments::default();
+        assert_eq!(assignments.join(3), Some(0));
+        assert_eq!(assignments.join(3), None);
+        assert_eq!(assignments.join(5), Some(1));
+        assert_eq!(assignments.player(3), Some(0));
+        assert_eq!(assignments.gamepad(1), Some(5));
+    }
+
+    #[test]
+    fn join_respects_max_players() {
+        let mut assignments = GamepadAssignments { max_players: 2, ..Default::default() };
+        assert_eq!(assignments.join(0), Some(0));
+        assert_eq!(assignments.join(1), Some(1));
+        assert_eq!(assignments.free_player(), None);
+        assert_eq!(assignments.join(2), None);
+        assert_eq!(assignments.player(2), None);
+
+        assert_eq!(assignments.unassign(0), Some(0));
+        assert_eq!(assignments.free_player(), Some(0));
+        assert_eq!(assignments.join(2), Some(0));
+    }
+
+    #[test]
+    fn assign_moves_gamepad_off_previous_player() {
+        let mut assignments = GamepadAssignments::default();
+        assignments.assign(0, 7);
+        assignments.assign(2, 7);
+        assert_eq!(assignments.gamepad(0), None);
+        assert_eq!(assignments.gamepad(2), Some(7));
+        assert_eq!(assignments.player(7), Some(2));
+        assert_eq!(assignments.free_player(), Some(0));
+    }
+}
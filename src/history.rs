@@ -0,0 +1,190 @@
+use std::time::Duration;
+use std::collections::VecDeque;
+use bevy::utils::HashMap;
+
+use crate::{Button, Event};
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct ButtonTiming {
+    pub pressed_at: Option<Duration>,
+    pub previous_pressed_at: Option<Duration>,
+    pub released_at: Option<Duration>
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct InputFrame {
+    pub time: Duration,
+    pub pressed: Vec<Button>,
+    pub just_pressed: Vec<Button>,
+    pub just_released: Vec<Button>
+}
+
+fn contains(buttons: &[Button], button: &Button) -> bool {
+    buttons.iter().any(|x| button.matches(x))
+}
+
+impl InputFrame {
+    pub fn matches(&self, event: &Event) -> bool {
+        match event {
+            Event::Pressed(btn) | Event::Value(btn) => contains(&self.pressed, btn),
+            Event::JustPressed(btn) => contains(&self.just_pressed, btn),
+            Event::JustReleased(btn) => contains(&self.just_released, btn),
+            Event::Chord(buttons) => buttons.iter().all(|btn| contains(&self.pressed, btn)),
+            _ => false
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InputHistory {
+    pub capacity: usize,
+    pub frames: VecDeque<InputFrame>,
+    pub timings: HashMap<Button, ButtonTiming>
+}
+
+impl Default for InputHistory {
+    fn default() -> InputHistory {
+        InputHistory {
+            capacity: 64,
+            frames: Default::default(),
+            timings: Default::default()
+        }
+    }
+}
+
+impl InputHistory {
+    pub fn push(&mut self, frame: InputFrame) {
+        for button in frame.just_pressed.iter() {
+            let timing = self.timings.entry(button.clone()).or_default();
+            timing.previous_pressed_at = timing.pressed_at;
+            timing.pressed_at = Some(frame.time);
+        }
+        for button in frame.just_released.iter() {
+            self.timings.entry(button.clone()).or_default().released_at = Some(frame.time);
+        }
+        self.frames.push_back(frame);
+        while self.frames.len() > self.capacity {
+            self.frames.pop_front();
+        }
+    }
+
+    pub fn latest(&self) -> Option<&InputFrame> {
+        self.frames.back()
+    }
+
+    pub fn timing(&self, button: &Button) -> ButtonTiming {
+        self.timings.get(button).copied().unwrap_or_default()
+    }
+
+    pub fn matches_sequence(&self, events: &[Event], window: Duration, now: Duration) -> bool {
+        let latest = match self.latest() {
+            Some(frame) if frame.time == now => frame,
+            _ => return false
+        };
+        let (last, rest) = match events.split_last() {
+            Some(split) => split,
+            None => return false
+        };
+        if !latest.matches(last) {
+            return false;
+        }
+        let mut frames = self.frames.iter().rev().skip(1);
+        let mut started = latest.time;
+        for event in rest.iter().rev() {
+            match frames.find(|frame| frame.matches(event)) {
+                Some(frame) => started = frame.time,
+                None => return false
+            }
+        }
+        now.saturating_sub(started) <= window
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::keyboard::KeyCode;
+    use super::*;
+
+    fn frame(ms: u64, key: KeyCode) -> InputFrame {
+        InputFrame {
+            time: Duration::from_millis(ms),
+            pressed: vec![Button::Keyboard(key)],
+            just_pressed: vec![Button::Keyboard(key)],
+            just_released: vec![]
+        }
+    }
+
+    fn sequence(keys: &[KeyCode]) -> Vec<Event> {
+        keys.iter().map(|key| Event::JustPressed(Button::Keyboard(*key))).collect()
+    }
+
+    fn history(frames: impl IntoIterator<Item = InputFrame>) -> InputHistory {
+        let mut history = InputHistory::default();
+        for frame in frames {
+            history.push(frame);
+        }
+        history
+    }
+
+    #[test]
+    fn sequence_matches_inside_window() {
+        let history = history([frame(0, KeyCode::A), frame(100, KeyCode::B), frame(200, KeyCode::C)]);
+        let events = sequence(&[KeyCode::A, KeyCode::B, KeyCode::C]);
+        let now = Duration::from_millis(200);
+        assert!(history.matches_sequence(&events, Duration::from_millis(200), now));
+        assert!(!history.matches_sequence(&events, Duration::from_millis(199), now));
+    }
+
+    #[test]
+    fn sequence_ends_on_current_frame() {
+        let history = history([frame(0, KeyCode::A), frame(100, KeyCode::B)]);
+        let events = sequence(&[KeyCode::A, KeyCode::B]);
+        let window = Duration::from_millis(500);
+        assert!(history.matches_sequence(&events, window, Duration::from_millis(100)));
+        assert!(!history.matches_sequence(&events, window, Duration::from_millis(150)));
+        assert!(!history.matches_sequence(&[], window, Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn sequence_keeps_order() {
+        let history = history([frame(0, KeyCode::A), frame(100, KeyCode::B), frame(200, KeyCode::C)]);
+        let now = Duration::from_millis(200);
+        let window = Duration::from_millis(500);
+        assert!(!history.matches_sequence(&sequence(&[KeyCode::B, KeyCode::A, KeyCode::C]), window, now));
+        assert!(history.matches_sequence(&sequence(&[KeyCode::A, KeyCode::C]), window, now));
+    }
+
+    #[test]
+    fn evicted_frames_do_not_match() {
+        let mut history = InputHistory { capacity: 2, ..Default::default() };
+        for frame in [frame(0, KeyCode::A), frame(100, KeyCode::B), frame(200, KeyCode::C)] {
+            history.push(frame);
+        }
+        let now = Duration::from_millis(200);
+        let window = Duration::from_millis(500);
+        assert_eq!(history.frames.len(), 2);
+        assert!(!history.matches_sequence(&sequence(&[KeyCode::A, KeyCode::B, KeyCode::C]), window, now));
+        assert!(history.matches_sequence(&sequence(&[KeyCode::B, KeyCode::C]), window, now));
+    }
+
+    #[test]
+    fn wildcards_match_recorded_buttons() {
+        let history = history([frame(0, KeyCode::A), frame(100, KeyCode::B)]);
+        let events = vec![Event::JustPressed(Button::AnyKey), Event::JustPressed(Button::Keyboard(KeyCode::B))];
+        assert!(history.matches_sequence(&events, Duration::from_millis(100), Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn timing_keeps_previous_press() {
+        let mut history = history([frame(0, KeyCode::A), frame(100, KeyCode::A)]);
+        history.push(InputFrame {
+            time: Duration::from_millis(150),
+            just_released: vec![Button::Keyboard(KeyCode::A)],
+            ..Default::default()
+        });
+        let timing = history.timing(&Button::Keyboard(KeyCode::A));
+        assert_eq!(timing.pressed_at, Some(Duration::from_millis(100)));
+        assert_eq!(timing.previous_pressed_at, Some(Duration::from_millis(0)));
+        assert_eq!(timing.released_at, Some(Duration::from_millis(150)));
+    }
+}
@@ -0,0 +1,336 @@
+use std::time::Duration;
+use bevy::math::Vec2;
+use bevy::input::{Input, Axis as InputAxis};
+use bevy::input::keyboard::KeyCode;
+use bevy::input::mouse::MouseButton;
+use bevy::input::gamepad::{Gamepad, GamepadAxis, GamepadButton};
+
+use crate::{Axis, BothHeld, Button, ConsumedInput, Device, DualAxis, Event, GamepadAssignments, InputHistory, MouseAxis, MouseInput};
+use crate::{Drag, Pointer, PointerInput, PointerPosition};
+
+#[derive(Clone, Copy)]
+pub(crate) struct InputSources<'a> {
+    pub keyboard: &'a Input<KeyCode>,
+    pub mouse: &'a Input<MouseButton>,
+    pub gamepad: &'a Input<GamepadButton>,
+    pub gamepad_axes: &'a InputAxis<GamepadAxis>,
+    pub gamepad_buttons: &'a InputAxis<GamepadButton>,
+    pub mouse_input: &'a MouseInput,
+    pub pointers: &'a PointerInput,
+    pub history: &'a InputHistory,
+    pub consumed: &'a ConsumedInput,
+    pub gamepads: &'a GamepadAssignments,
+    pub player: usize,
+    pub now: Duration
+}
+
+impl <'a>InputSources<'a> {
+    fn matching(&self, button: &Button) -> Vec<Button> {
+        match button {
+            Button::PlayerGamepad(btn) => self.gamepads.gamepad(self.player)
+                .map(|id| Button::Gamepad(id, *btn))
+                .into_iter()
+                .collect(),
+            Button::AnyGamepad(btn) => self.gamepads.connected.iter()
+                .map(|id| Button::Gamepad(*id, *btn))
+                .collect(),
+            Button::AnyKey => self.keyboard.get_pressed()
+                .chain(self.keyboard.get_just_released())
+                .map(|code| Button::Keyboard(*code))
+                .collect(),
+            Button::AnyMouse => self.mouse.get_pressed()
+                .chain(self.mouse.get_just_released())
+                .map(|btn| Button::Mouse(*btn))
+                .collect(),
+            Button::Any => {
+                let mut buttons = self.matching(&Button::AnyKey);
+                buttons.extend(self.matching(&Button::AnyMouse));
+                buttons.extend(self.gamepad.get_pressed()
+                    .chain(self.gamepad.get_just_released())
+                    .map(|btn| Button::Gamepad(btn.0.0, btn.1)));
+                buttons
+            },
+            _ => vec![button.clone()]
+        }
+    }
+
+    fn matching_axes(&self, axis: &Axis) -> Vec<Axis> {
+        match axis {
+            Axis::PlayerGamepad(x) => self.gamepads.gamepad(self.player)
+                .map(|id| Axis::Gamepad(id, *x))
+                .into_iter()
+                .collect(),
+            Axis::AnyGamepad(x) => self.gamepads.connected.iter()
+                .map(|id| Axis::Gamepad(*id, *x))
+                .collect(),
+            _ => vec![axis.clone()]
+        }
+    }
+
+    fn player_button(&self, button: &Button) -> Button {
+        match button {
+            Button::PlayerGamepad(btn) => self.gamepads.gamepad(self.player)
+                .map(|id| Button::Gamepad(id, *btn))
+                .unwrap_or_else(|| button.clone()),
+            _ => button.clone()
+        }
+    }
+
+    fn player_event(&self, event: &Event) -> Event {
+        match event {
+            Event::Pressed(btn) => Event::Pressed(self.player_button(btn)),
+            Event::JustPressed(btn) => Event::JustPressed(self.player_button(btn)),
+            Event::JustReleased(btn) => Event::JustReleased(self.player_button(btn)),
+            Event::Value(btn) => Event::Value(self.player_button(btn)),
+            Event::Chord(buttons) => Event::Chord(buttons.iter().map(|btn| self.player_button(btn)).collect()),
+            _ => event.clone()
+        }
+    }
+
+    pub fn pressed(&self, button: &Button) -> bool {
+        self.matching(button).iter().any(|button| !self.consumed.is_consumed(button) && match button {
+            Button::Keyboard(code) => self.keyboard.pressed(*code),
+            Button::Mouse(btn) => self.mouse.pressed(*btn),
+            Button::Gamepad(id, btn) => self.gamepad.pressed(GamepadButton(Gamepad(*id), *btn)),
+            Button::Wheel(direction) => self.mouse_input.scrolled(*direction),
+            _ => false
+        })
+    }
+
+    pub fn just_pressed(&self, button: &Button) -> bool {
+        self.matching(button).iter().any(|button| !self.consumed.is_consumed(button) && match button {
+            Button::Keyboard(code) => self.keyboard.just_pressed(*code),
+            Button::Mouse(btn) => self.mouse.just_pressed(*btn),
+            Button::Gamepad(id, btn) => self.gamepad.just_pressed(GamepadButton(Gamepad(*id), *btn)),
+            Button::Wheel(direction) => self.mouse_input.scrolled(*direction),
+            _ => false
+        })
+    }
+
+    pub fn just_released(&self, button: &Button) -> bool {
+        self.matching(button).iter().any(|button| !self.consumed.is_consumed(button) && match button {
+            Button::Keyboard(code) => self.keyboard.just_released(*code),
+            Button::Mouse(btn) => self.mouse.just_released(*btn),
+            Button::Gamepad(id, btn) => self.gamepad.just_released(GamepadButton(Gamepad(*id), *btn)),
+            Button::Wheel(direction) => self.mouse_input.just_stopped(*direction),
+            _ => false
+        })
+    }
+
+    pub fn button_value(&self, button: &Button) -> f32 {
+        self.matching(button).iter()
+            .filter(|button| !self.consumed.is_consumed(button))
+            .map(|button| match button {
+                Button::Gamepad(id, btn) => self.gamepad_buttons
+                    .get(GamepadButton(Gamepad(*id), *btn))
+                    .unwrap_or(0.0),
+                _ => if self.pressed(button) { 1.0 } else { 0.0 }
+            })
+            .fold(0.0, f32::max)
+    }
+
+    pub fn axis_value(&self, axis: &Axis) -> f32 {
+        if self.consumed.is_axis_consumed(axis) {
+            return 0.0;
+        }
+        self.matching_axes(axis).iter()
+            .map(|axis| match axis {
+                Axis::Mouse(MouseAxis::X) => self.mouse_input.motion.x,
+                Axis::Mouse(MouseAxis::Y) => self.mouse_input.motion.y,
+                Axis::Mouse(MouseAxis::ScrollX) => self.mouse_input.scroll.x,
+                Axis::Mouse(MouseAxis::ScrollY) => self.mouse_input.scroll.y,
+                Axis::Gamepad(id, axis) => self.gamepad_axes
+                    .get(GamepadAxis(Gamepad(*id), *axis))
+                    .unwrap_or(0.0),
+                _ => 0.0
+            })
+            .fold(0.0, |current, value| if value.abs() > current.abs() { value } else { current })
+    }
+
+    fn pressed_at(&self, button: &Button) -> Option<Duration> {
+        self.matching(button).iter()
+            .filter_map(|button| self.history.timing(button).pressed_at)
+            .max()
+    }
+
+    pub fn evaluate_pointer(&self, event: &Event) -> Option<PointerPosition> {
+        match event {
+            Event::Pointer(Pointer::Cursor) if self.consumed.devices.contains(&Device::Mouse) => None,
+            Event::Pointer(pointer) => self.pointers.get(*pointer).copied(),
+            _ => None
+        }
+    }
+
+    pub fn evaluate_drag(&self, event: &Event) -> Option<Drag> {
+        let (button, pointer, threshold) = match event {
+            Event::Drag { button, pointer, threshold_px } => (button, *pointer, *threshold_px as f32),
+            _ => return None
+        };
+        self.matching(button).iter()
+            .filter(|button| self.pressed(button))
+            .filter_map(|button| self.pointers.drag(button, pointer))
+            .find(|drag| drag.max_distance >= threshold)
+            .map(Drag::from)
+    }
+
+    pub fn evaluate_dual(&self, event: &Event) -> Option<Vec2> {
+        let (input, settings) = match event {
+            Event::DualAxis { input, settings } => (input, settings),
+            _ => return None
+        };
+        let value = match input {
+            DualAxis::Axes { x, y } => Vec2::new(self.axis_value(x), self.axis_value(y)),
+            DualAxis::Buttons { up, down, left, right } => Vec2::new(
+                self.button_value(right) - self.button_value(left),
+                self.button_value(up) - self.button_value(down)
+            ).clamp_length_max(1.0)
+        };
+        let value = settings.apply(value);
+        (value != Vec2::ZERO).then(|| value)
+    }
+
+    pub fn evaluate(&self, event: &Event) -> Option<f32> {
+        let since = |at: Option<Duration>| at.map(|at| self.now.saturating_sub(at));
+        let active = match event {
+            Event::Pressed(btn) => self.pressed(btn),
+            Event::JustPressed(btn) => self.just_pressed(btn),
+            Event::JustReleased(btn) => self.just_released(btn),
+            Event::Value(btn) => {
+                let value = self.button_value(btn);
+                return (value != 0.0).then(|| value);
+            },
+            Event::Axis(axis) => {
+                let value = self.axis_value(axis);
+                return (value != 0.0).then(|| value);
+            },
+            Event::VirtualAxis { negative, positive, both } => {
+                let (negative_value, positive_value) = (self.button_value(negative), self.button_value(positive));
+                let value = if negative_value == 0.0 || positive_value == 0.0 {
+                    positive_value - negative_value
+                } else {
+                    let positive_last = self.pressed_at(positive) >= self.pressed_at(negative);
+                    match both {
+                        BothHeld::Cancel => 0.0,
+                        BothHeld::LastPressed if positive_last => positive_value,
+                        BothHeld::FirstPressed if !positive_last => positive_value,
+                        _ => -negative_value
+                    }
+                };
+                return (value != 0.0).then(|| value);
+            },
+            Event::Pointer(_) => self.evaluate_pointer(event).is_some(),
+            Event::Drag { .. } => self.evaluate_drag(event).is_some(),
+            Event::DualAxis { .. } => return self.evaluate_dual(event).map(|value| value.length()),
+            Event::Processed { event, settings } => {
+                let value = settings.apply(self.evaluate(event).unwrap_or(0.0));
+                return (value != 0.0).then(|| value);
+            },
+            Event::Chord(buttons) => buttons.iter().all(|btn| self.pressed(btn)),
+            Event::Sequence { events, window_ms } => {
+                let events: Vec<Event> = events.iter().map(|event| self.player_event(event)).collect();
+                self.history.matches_sequence(&events, Duration::from_millis(*window_ms), self.now)
+            },
+            Event::DoubleTap { button, interval_ms } => self.matching(button).iter().any(|button| {
+                let timing = self.history.timing(button);
+                timing.pressed_at == Some(self.now) && since(timing.previous_pressed_at)
+                    .map(|x| x <= Duration::from_millis(*interval_ms))
+                    .unwrap_or(false)
+            }),
+            Event::LongPress { button, hold_ms } => self.matching(button).iter().any(|button| {
+                self.pressed(button) && since(self.history.timing(button).pressed_at)
+                    .map(|x| x >= Duration::from_millis(*hold_ms))
+                    .unwrap_or(false)
+            }),
+            Event::Tap { button, max_ms } => self.matching(button).iter().any(|button| {
+                let timing = self.history.timing(button);
+                timing.released_at == Some(self.now) && since(timing.pressed_at)
+                    .map(|x| x <= Duration::from_millis(*max_ms))
+                    .unwrap_or(false)
+            })
+        };
+        active.then(|| 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::gamepad::GamepadButtonType;
+    use super::*;
+    use crate::WheelDirection;
+    use crate::tests::TestInput;
+
+    #[test]
+    fn wildcards_only_see_their_device() {
+        let mut input = TestInput::default();
+        input.mouse.press(MouseButton::Left);
+        input.keyboard.press(KeyCode::Space);
+        input.keyboard.clear();
+        input.keyboard.release(KeyCode::Space);
+        let sources = input.sources();
+        assert!(!sources.pressed(&Button::AnyKey));
+        assert!(sources.just_released(&Button::AnyKey));
+        assert!(sources.pressed(&Button::AnyMouse));
+        assert!(!sources.pressed(&Button::AnyGamepad(GamepadButtonType::South)));
+        assert!(sources.pressed(&Button::Any));
+        assert_eq!(sources.matching(&Button::AnyMouse), vec![Button::Mouse(MouseButton::Left)]);
+    }
+
+    #[test]
+    fn any_excludes_wheel() {
+        let mut input = TestInput::default();
+        input.mouse_input.scroll = Vec2::new(0.0, 1.0);
+        let sources = input.sources();
+        assert!(sources.pressed(&Button::Wheel(WheelDirection::Up)));
+        assert!(!sources.pressed(&Button::AnyMouse));
+        assert!(!sources.pressed(&Button::Any));
+        assert!(sources.matching(&Button::Any).is_empty());
+    }
+
+    #[test]
+    fn any_gamepad_matches_connected_pads() {
+        let mut input = TestInput::default();
+        input.gamepads.connected = vec![0, 2];
+        input.gamepad.press(GamepadButton(Gamepad(2), GamepadButtonType::South));
+        let sources = input.sources();
+        assert_eq!(sources.matching(&Button::AnyGamepad(GamepadButtonType::South)), vec![
+            Button::Gamepad(0, GamepadButtonType::South),
+            Button::Gamepad(2, GamepadButtonType::South)
+        ]);
+        assert!(sources.pressed(&Button::AnyGamepad(GamepadButtonType::South)));
+        assert!(!sources.pressed(&Button::AnyGamepad(GamepadButtonType::East)));
+        assert!(!sources.pressed(&Button::AnyKey));
+        assert_eq!(sources.matching(&Button::Any), vec![Button::Gamepad(2, GamepadButtonType::South)]);
+    }
+
+    #[test]
+    fn drag_waits_for_threshold_then_persists() {
+        let button = Button::Mouse(MouseButton::Left);
+        let event = Event::Drag { button: button.clone(), pointer: Pointer::Cursor, threshold_px: 5 };
+        let mut input = TestInput::default();
+        input.mouse.press(MouseButton::Left);
+        let mut state = crate::DragState::new(Vec2::ZERO);
+        input.pointers.drags.insert((button.clone(), Pointer::Cursor), state);
+        assert!(input.sources().evaluate_drag(&event).is_none());
+
+        state.update(Vec2::new(4.0, 0.0));
+        input.pointers.drags.insert((button.clone(), Pointer::Cursor), state);
+        assert!(input.sources().evaluate_drag(&event).is_none());
+
+        state.update(Vec2::new(6.0, 0.0));
+        input.pointers.drags.insert((button.clone(), Pointer::Cursor), state);
+        assert_eq!(input.sources().evaluate_drag(&event).map(|drag| drag.delta), Some(Vec2::new(2.0, 0.0)));
+
+        state.update(Vec2::new(1.0, 0.0));
+        input.pointers.drags.insert((button.clone(), Pointer::Cursor), state);
+        let drag = input.sources().evaluate_drag(&event);
+        assert_eq!(drag.map(|drag| drag.displacement()), Some(Vec2::new(1.0, 0.0)));
+        assert!(input.sources().evaluate_drag(&Event::Drag {
+            button: button.clone(),
+            pointer: Pointer::Virtual,
+            threshold_px: 0
+        }).is_none());
+
+        input.mouse.release(MouseButton::Left);
+        assert!(input.sources().evaluate_drag(&event).is_none());
+    }
+}
@@ -0,0 +1,73 @@
+use std::hash::Hash;
+use bevy::utils::HashMap;
+use serde::{ Serialize, Deserialize };
+
+use crate::{CombinePolicy, Event};
+
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, PartialOrd, Ord)]
+pub enum ConfigLayer {
+    Defaults,
+    Asset,
+    Platform,
+    User
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, bound(deserialize = "K: Deserialize<'de>"))]
+pub struct LayerBindings<K: Eq + Hash> {
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub add: HashMap<K, Vec<Event>>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub replace: HashMap<K, Vec<Event>>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub unbind: HashMap<K, Vec<Event>>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub combine: HashMap<K, CombinePolicy>
+}
+
+fn convert_keys<K, T: Eq + Hash, V, E>(
+    map: HashMap<K, V>,
+    f: &impl Fn(K) -> Result<T, E>
+) -> Result<HashMap<T, V>, E> {
+    map.into_iter().map(|(key, value)| Ok((f(key)?, value))).collect()
+}
+
+impl <K: Eq + Hash>Default for LayerBindings<K> {
+    fn default() -> LayerBindings<K> {
+        LayerBindings {
+            add: Default::default(),
+            replace: Default::default(),
+            unbind: Default::default(),
+            combine: Default::default()
+        }
+    }
+}
+
+impl <K: Eq + Hash>LayerBindings<K> {
+    pub fn added(add: HashMap<K, Vec<Event>>) -> LayerBindings<K> {
+        LayerBindings { add, ..Default::default() }
+    }
+
+    pub fn replaced(replace: HashMap<K, Vec<Event>>) -> LayerBindings<K> {
+        LayerBindings { replace, ..Default::default() }
+    }
+
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        self.add.values()
+            .chain(self.replace.values())
+            .flatten()
+            .try_for_each(Event::validate)
+    }
+
+    pub fn try_map_keys<T: Eq + Hash, E>(
+        self,
+        f: impl Fn(K) -> Result<T, E>
+    ) -> Result<LayerBindings<T>, E> {
+        Ok(LayerBindings {
+            add: convert_keys(self.add, &f)?,
+            replace: convert_keys(self.replace, &f)?,
+            unbind: convert_keys(self.unbind, &f)?,
+            combine: convert_keys(self.combine, &f)?
+        })
+    }
+}
@@ -7,19 +7,53 @@ pub use bevy_actions_derive::*;
 use serde::{ Serialize, de::DeserializeOwned };
 
 mod event;
-pub use self::event::{Button, Event, Axis, MouseAxis};
+pub use self::event::{Button, Event, Axis, BothHeld, DualAxis, MouseAxis};
+
+mod processing;
+pub use self::processing::{AxisProcessing, DualAxisProcessing, ResponseCurve};
+
+mod mouse;
+pub use self::mouse::{MouseInput, WheelDirection};
+
+mod pointer;
+pub use self::pointer::{Pointer, PointerRay, PointerPosition, PointerCamera, PointerInput, VirtualCursor, Drag, DragState};
+
+mod consume;
+pub use self::consume::{ConsumedInput, Device};
+
+mod input;
+
+mod bindings;
+
+mod gamepad;
+pub use self::gamepad::{GamepadAssignments, GamepadAssignmentEvent};
+
+mod history;
+pub use self::history::{InputHistory, InputFrame, ButtonTiming};
+
+mod context;
+pub use self::context::{ActionContext, ActionContexts};
+
+mod user;
+pub use self::user::{UserBindings, user_config_path};
+
+mod rebind;
+pub use self::rebind::{ActionsRebind, Capture, CaptureKind, RebindEvent};
 
 mod plugin;
-pub use self::plugin::ConfigActionsPlugin;
+pub use self::plugin::{ConfigActionsPlugin, ConsumeUiInputPlugin, ActionsSystem};
 
 mod loader;
 pub use self::loader::ConfigActionsLoader;
 
 mod actions;
-pub use self::actions::Actions;
+pub use self::actions::{Actions, ActionState, ActionPhase, ActionStatus, InputMap};
+
+mod layer;
+pub use self::layer::{ConfigLayer, LayerBindings};
 
 mod config;
-pub use self::config::ActionsConfig;
+pub use self::config::{ActionsConfig, ActionsConfigChanged, CombinePolicy};
 
 pub trait ConfigActionsRequirements:
     Eq + Hash + TypeUuid +
@@ -39,7 +73,67 @@ where T:
 pub trait ConfigActions: ConfigActionsRequirements {
     const PATH: &'static str;
 
-    fn default_bindings() -> HashMap<Event, Self> {
+    fn default_bindings() -> HashMap<Self, Vec<Event>> {
         Default::default()
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::time::Duration;
+    use bevy::reflect::TypeUuid;
+    use bevy::input::{Input, Axis as InputAxis};
+    use bevy::input::keyboard::KeyCode;
+    use bevy::input::mouse::MouseButton;
+    use bevy::input::gamepad::{GamepadAxis, GamepadButton};
+    use serde::{ Serialize, Deserialize };
+
+    use crate::{ConsumedInput, GamepadAssignments, InputHistory, MouseInput, PointerInput};
+    use crate::input::InputSources;
+
+    #[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, Serialize, Deserialize, TypeUuid)]
+    #[uuid = "6b1e9bd0-2d0e-4f0a-9f43-1f7c8f6a2d11"]
+    pub enum TestActions {
+        Jump,
+        Fire,
+        Confirm
+    }
+
+    impl crate::ConfigActions for TestActions {
+        const PATH: &'static str = "test.ron";
+    }
+
+    #[derive(Default)]
+    pub(crate) struct TestInput {
+        pub keyboard: Input<KeyCode>,
+        pub mouse: Input<MouseButton>,
+        pub gamepad: Input<GamepadButton>,
+        pub gamepad_axes: InputAxis<GamepadAxis>,
+        pub gamepad_buttons: InputAxis<GamepadButton>,
+        pub mouse_input: MouseInput,
+        pub pointers: PointerInput,
+        pub history: InputHistory,
+        pub consumed: ConsumedInput,
+        pub gamepads: GamepadAssignments,
+        pub now: Duration
+    }
+
+    impl TestInput {
+        pub(crate) fn sources(&self) -> InputSources {
+            InputSources {
+                keyboard: &self.keyboard,
+                mouse: &self.mouse,
+                gamepad: &self.gamepad,
+                gamepad_axes: &self.gamepad_axes,
+                gamepad_buttons: &self.gamepad_buttons,
+                mouse_input: &self.mouse_input,
+                pointers: &self.pointers,
+                history: &self.history,
+                consumed: &self.consumed,
+                gamepads: &self.gamepads,
+                player: 0,
+                now: self.now
+            }
+        }
+    }
+}
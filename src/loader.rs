@@ -1,13 +1,83 @@
-use bevy::utils::{HashMap, BoxedFuture};
+use std::fmt::Display;
+use std::hash::Hash;
+use std::path::PathBuf;
+use bevy::log::warn;
+use bevy::utils::{BoxedFuture, HashMap};
 use bevy::asset::{LoadedAsset, AssetLoader, LoadContext};
 
-use crate::{Event, ConfigActions, ActionsConfig};
+use crate::{ConfigActions, ActionsConfig, ConfigLayer, Event, LayerBindings};
 
-pub struct ConfigActionsLoader<T: ConfigActions>(std::marker::PhantomData<T>);
+const EXTENSIONS: &[&str] = &[
+    "ron",
+    #[cfg(feature = "toml")]
+    "toml",
+    #[cfg(feature = "json")]
+    "json",
+    #[cfg(feature = "yaml")]
+    "yaml",
+    #[cfg(feature = "yaml")]
+    "yml",
+];
+
+fn layer_or_map<K: Eq + Hash, E: Display>(
+    layer: impl FnOnce() -> Result<LayerBindings<K>, E>,
+    map: impl FnOnce() -> Result<HashMap<K, Vec<Event>>, E>
+) -> Result<LayerBindings<K>, anyhow::Error> {
+    layer().or_else(|layer_err| map().map(LayerBindings::added).map_err(|map_err| anyhow::anyhow!(
+        "Not a valid config layer ({}) or map of actions to events ({})", layer_err, map_err
+    )))
+}
+
+pub struct ConfigActionsLoader<T: ConfigActions> {
+    pub user_path: Option<PathBuf>,
+    marker: std::marker::PhantomData<T>
+}
 
 impl <T: ConfigActions>Default for ConfigActionsLoader<T> {
     fn default() -> ConfigActionsLoader<T> {
-        ConfigActionsLoader(Default::default())
+        ConfigActionsLoader::new(None)
+    }
+}
+
+impl <T: ConfigActions>ConfigActionsLoader<T> {
+    pub fn new(user_path: Option<PathBuf>) -> ConfigActionsLoader<T> {
+        ConfigActionsLoader { user_path, marker: Default::default() }
+    }
+
+    fn user_layer(&self) -> Option<LayerBindings<T>> {
+        let path = self.user_path.as_ref()?;
+        let bytes = std::fs::read(path).ok()?;
+        let extension = path.extension().and_then(|x|x.to_str()).unwrap_or("ron");
+        match Self::parse(extension, &bytes) {
+            Ok(layer) => Some(layer),
+            Err(err) => {
+                warn!("Failed to load user bindings from {:?}: {}", path, err);
+                None
+            }
+        }
+    }
+
+    pub fn parse(extension: &str, bytes: &[u8]) -> Result<LayerBindings<T>, anyhow::Error> {
+        let layer = Self::parse_format(extension, bytes)?;
+        layer.validate()?;
+        Ok(layer)
+    }
+
+    fn parse_format(extension: &str, bytes: &[u8]) -> Result<LayerBindings<T>, anyhow::Error> {
+        match extension {
+            #[cfg(feature = "toml")]
+            "toml" => {
+                use serde::de::{IntoDeserializer, value::Error};
+                // TOML keys are always strings, so the action names are converted separately.
+                let layer: LayerBindings<String> = layer_or_map(|| toml::from_slice(bytes), || toml::from_slice(bytes))?;
+                Ok(layer.try_map_keys(|key| T::deserialize(IntoDeserializer::<Error>::into_deserializer(key)))?)
+            },
+            #[cfg(feature = "json")]
+            "json" => layer_or_map(|| serde_json::from_slice(bytes), || serde_json::from_slice(bytes)),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => layer_or_map(|| serde_yaml::from_slice(bytes), || serde_yaml::from_slice(bytes)),
+            _ => layer_or_map(|| ron::de::from_bytes(bytes), || ron::de::from_bytes(bytes))
+        }
     }
 }
 
@@ -18,19 +88,100 @@ impl <T: ConfigActions>AssetLoader for ConfigActionsLoader<T> {
         load_context: &'a mut LoadContext,
     ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
         Box::pin(async move {
-            let config_asset = ron::de::from_bytes::<HashMap<T, Vec<Event>>>(bytes)?;
-            let mut data = T::default_bindings();
-            for (action, events) in config_asset.into_iter() {
-                events.into_iter().for_each(| event | {
-                    data.insert(event, action.clone());
-                })
+            let path = load_context.path().to_path_buf();
+            let extension = path.extension()
+                .and_then(|x|x.to_str())
+                .unwrap_or("ron");
+            let mut config = ActionsConfig::<T>::default();
+            config.validate()?;
+            config.push_layer(ConfigLayer::Asset, Self::parse(extension, bytes)?);
+            let platform_path = path.with_extension(format!("{}.{}", std::env::consts::OS, extension));
+            let platform_bytes = load_context.read_asset_bytes(&platform_path).await.ok();
+            if let Some(platform_bytes) = platform_bytes.as_ref() {
+                config.push_layer(ConfigLayer::Platform, Self::parse(extension, platform_bytes)?);
+            }
+            if let Some(layer) = self.user_layer() {
+                config.push_layer(ConfigLayer::User, layer);
             }
-            load_context.set_default_asset(LoadedAsset::new(ActionsConfig { data }));
+            let mut asset = LoadedAsset::new(config);
+            if platform_bytes.is_some() {
+                asset = asset.with_dependency(platform_path.into());
+            }
+            load_context.set_default_asset(asset);
             Ok(())
         })
     }
 
     fn extensions(&self) -> &[&str] {
-        &["ron"]
+        EXTENSIONS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::keyboard::KeyCode;
+    use super::*;
+    use crate::{Button, CombinePolicy};
+    use crate::tests::TestActions;
+
+    type Loader = ConfigActionsLoader<TestActions>;
+
+    fn key(key: KeyCode) -> Event {
+        Event::Pressed(Button::Keyboard(key))
+    }
+
+    fn assert_map(layer: LayerBindings<TestActions>) {
+        assert_eq!(layer, LayerBindings::added([(TestActions::Jump, vec![key(KeyCode::Space)])].into_iter().collect()));
+    }
+
+    fn assert_layer(layer: LayerBindings<TestActions>) {
+        assert!(layer.add.is_empty());
+        assert_eq!(layer.replace[&TestActions::Jump], vec![key(KeyCode::Up)]);
+    }
+
+    #[test]
+    fn ron_plain_map_is_added() {
+        assert_map(Loader::parse("ron", b"{ Jump: [Pressed(Keyboard(Space))] }").unwrap());
+    }
+
+    #[test]
+    fn ron_layer() {
+        let layer = Loader::parse("ron", b"(replace: { Jump: [Pressed(Keyboard(Up))] }, combine: { Jump: Sum })").unwrap();
+        assert_layer(layer.clone());
+        assert_eq!(layer.combine[&TestActions::Jump], CombinePolicy::Sum);
+    }
+
+    #[test]
+    fn invalid_file_reports_both_errors() {
+        let err = Loader::parse("ron", b"(jump: 1)").unwrap_err().to_string();
+        assert!(err.contains("Not a valid config layer (unknown field `jump`"), "{}", err);
+        assert!(err.contains("or map of actions to events ("), "{}", err);
+    }
+
+    #[test]
+    fn unknown_extension_is_parsed_as_ron() {
+        assert_map(Loader::parse("cfg", b"{ Jump: [Pressed(Keyboard(Space))] }").unwrap());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_keys_are_converted_to_actions() {
+        assert_map(Loader::parse("toml", b"Jump = [{ Pressed = { Keyboard = \"Space\" } }]\n").unwrap());
+        assert_layer(Loader::parse("toml", b"[replace]\nJump = [{ Pressed = { Keyboard = \"Up\" } }]\n").unwrap());
+        assert!(Loader::parse("toml", b"Jumps = [{ Pressed = { Keyboard = \"Up\" } }]\n").is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_map_and_layer() {
+        assert_map(Loader::parse("json", br#"{ "Jump": [{ "Pressed": { "Keyboard": "Space" } }] }"#).unwrap());
+        assert_layer(Loader::parse("json", br#"{ "replace": { "Jump": [{ "Pressed": { "Keyboard": "Up" } }] } }"#).unwrap());
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_map_and_layer() {
+        assert_map(Loader::parse("yaml", b"Jump:\n  - Pressed:\n      Keyboard: Space\n").unwrap());
+        assert_layer(Loader::parse("yml", b"replace:\n  Jump:\n    - Pressed:\n        Keyboard: Up\n").unwrap());
     }
 }
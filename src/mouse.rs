@@ -0,0 +1,160 @@
+use std::collections::VecDeque;
+use bevy::math::Vec2;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use serde::{ Serialize, Deserialize };
+
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, Serialize, Deserialize)]
+pub enum WheelDirection {
+    Up,
+    Down,
+    Left,
+    Right
+}
+
+impl WheelDirection {
+    pub const ALL: [WheelDirection; 4] = [
+        WheelDirection::Up, WheelDirection::Down, WheelDirection::Left, WheelDirection::Right
+    ];
+
+    pub fn scrolled(&self, scroll: Vec2) -> bool {
+        match self {
+            WheelDirection::Up => scroll.y > 0.0,
+            WheelDirection::Down => scroll.y < 0.0,
+            WheelDirection::Left => scroll.x < 0.0,
+            WheelDirection::Right => scroll.x > 0.0
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MouseInput {
+    pub motion: Vec2,
+    pub raw_motion: Vec2,
+    pub smoothing_frames: usize,
+    pub recent_motion: VecDeque<Vec2>,
+    pub scroll: Vec2,
+    pub previous_scroll: Vec2,
+    pub pixels_per_line: f32
+}
+
+impl Default for MouseInput {
+    fn default() -> MouseInput {
+        MouseInput {
+            motion: Vec2::ZERO,
+            raw_motion: Vec2::ZERO,
+            smoothing_frames: 1,
+            recent_motion: Default::default(),
+            scroll: Vec2::ZERO,
+            previous_scroll: Vec2::ZERO,
+            pixels_per_line: 100.0
+        }
+    }
+}
+
+impl MouseInput {
+    pub(crate) fn push_motion(&mut self, delta: Vec2) {
+        let frames = self.smoothing_frames.max(1);
+        self.raw_motion = delta;
+        self.recent_motion.push_back(delta);
+        while self.recent_motion.len() > frames {
+            self.recent_motion.pop_front();
+        }
+        self.motion = self.recent_motion.iter().fold(Vec2::ZERO, |acc, x| acc + *x) / self.recent_motion.len() as f32;
+    }
+
+    pub(crate) fn push_scroll<'a>(&mut self, events: impl Iterator<Item = &'a MouseWheel>) {
+        let pixels_per_line = self.pixels_per_line;
+        self.previous_scroll = self.scroll;
+        self.scroll = events.fold(Vec2::ZERO, |acc, event| {
+            let delta = Vec2::new(event.x, event.y);
+            acc + match event.unit {
+                MouseScrollUnit::Line => delta,
+                MouseScrollUnit::Pixel => delta / pixels_per_line
+            }
+        });
+    }
+
+    pub fn scrolled(&self, direction: WheelDirection) -> bool {
+        direction.scrolled(self.scroll)
+    }
+
+    pub fn just_stopped(&self, direction: WheelDirection) -> bool {
+        direction.scrolled(self.previous_scroll) && !direction.scrolled(self.scroll)
+    }
+
+    pub fn scrolled_directions(&self) -> Vec<WheelDirection> {
+        WheelDirection::ALL.into_iter()
+            .filter(|direction| self.scrolled(*direction))
+            .collect()
+    }
+
+    pub fn stopped_directions(&self) -> Vec<WheelDirection> {
+        WheelDirection::ALL.into_iter()
+            .filter(|direction| self.just_stopped(*direction))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn motion_resets_on_still_frame() {
+        let mut mouse = MouseInput::default();
+        mouse.push_motion(Vec2::new(5.0, -2.0));
+        assert_eq!(mouse.motion, Vec2::new(5.0, -2.0));
+        mouse.push_motion(Vec2::ZERO);
+        assert_eq!(mouse.motion, Vec2::ZERO);
+        assert_eq!(mouse.raw_motion, Vec2::ZERO);
+    }
+
+    #[test]
+    fn smoothing_averages_recent_frames() {
+        let mut mouse = MouseInput { smoothing_frames: 3, ..Default::default() };
+        mouse.push_motion(Vec2::new(3.0, 0.0));
+        assert_eq!(mouse.motion, Vec2::new(3.0, 0.0));
+        mouse.push_motion(Vec2::new(6.0, 0.0));
+        assert_eq!(mouse.motion, Vec2::new(4.5, 0.0));
+        mouse.push_motion(Vec2::ZERO);
+        assert_eq!(mouse.motion, Vec2::new(3.0, 0.0));
+        mouse.push_motion(Vec2::ZERO);
+        assert_eq!(mouse.motion, Vec2::new(2.0, 0.0));
+        assert_eq!(mouse.raw_motion, Vec2::ZERO);
+        mouse.push_motion(Vec2::ZERO);
+        assert_eq!(mouse.motion, Vec2::ZERO);
+    }
+
+    fn wheel(unit: MouseScrollUnit, x: f32, y: f32) -> MouseWheel {
+        MouseWheel { unit, x, y }
+    }
+
+    #[test]
+    fn pixel_scroll_is_converted_to_lines() {
+        let mut mouse = MouseInput { pixels_per_line: 50.0, ..Default::default() };
+        let events = [wheel(MouseScrollUnit::Line, 0.0, 1.0), wheel(MouseScrollUnit::Pixel, -25.0, 100.0)];
+        mouse.push_scroll(events.iter());
+        assert_eq!(mouse.scroll, Vec2::new(-0.5, 3.0));
+    }
+
+    #[test]
+    fn scroll_directions() {
+        let mut mouse = MouseInput::default();
+        mouse.push_scroll([wheel(MouseScrollUnit::Line, 1.0, -2.0)].iter());
+        assert_eq!(mouse.scrolled_directions(), vec![WheelDirection::Down, WheelDirection::Right]);
+        assert!(mouse.stopped_directions().is_empty());
+
+        mouse.push_scroll([wheel(MouseScrollUnit::Line, 0.0, 1.0)].iter());
+        assert_eq!(mouse.scrolled_directions(), vec![WheelDirection::Up]);
+        assert!(mouse.just_stopped(WheelDirection::Down));
+        assert!(mouse.just_stopped(WheelDirection::Right));
+        assert!(!mouse.just_stopped(WheelDirection::Up));
+
+        mouse.push_scroll(std::iter::empty());
+        assert!(mouse.scrolled_directions().is_empty());
+        assert_eq!(mouse.stopped_directions(), vec![WheelDirection::Up]);
+
+        mouse.push_scroll(std::iter::empty());
+        assert!(mouse.stopped_directions().is_empty());
+    }
+}
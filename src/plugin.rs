@@ -1,28 +1,126 @@
+use std::path::{Path, PathBuf};
 use bevy::prelude::*;
 use bevy::asset::AssetServer;
 use bevy::app::{Plugin, App};
-use bevy::input::mouse::MouseMotion;
-use crate::{ Event, Axis, Button, MouseAxis, ConfigActions, Actions, ActionsConfig, ConfigActionsLoader };
+use bevy::utils::{HashMap, HashSet};
+use bevy::input::Axis as InputAxis;
+use bevy::input::InputSystem;
+use bevy::input::mouse::{MouseMotion, MouseWheel};
+use crate::{ Event, Axis, Button, MouseAxis, ConfigActions, Actions, ActionsConfig, ConfigActionsLoader, ActionsConfigChanged, InputHistory, InputFrame, ConsumedInput, Device };
+use crate::{ ActionsRebind, CaptureKind, RebindEvent, UserBindings, ActionContexts, user_config_path };
+use crate::{ ActionState, InputMap, CombinePolicy, GamepadAssignments, GamepadAssignmentEvent, MouseInput };
+use crate::{ Drag, DragState, Pointer, PointerCamera, PointerInput, PointerPosition, PointerRay, VirtualCursor };
+use crate::input::InputSources;
 
-pub struct ConfigActionsPlugin<A: ConfigActions>(std::marker::PhantomData<A>);
+#[derive(Debug, PartialEq, Clone, Eq, Hash, SystemLabel)]
+pub enum ActionsSystem {
+    ReloadConfig,
+    ConsumeInput,
+    Rebind,
+    AssignGamepads,
+    ReadMouse,
+    MoveCursor,
+    UpdatePointers,
+    RecordHistory,
+    HandleInput,
+    UpdateState
+}
+
+pub struct ConfigActionsPlugin<A: ConfigActions> {
+    pub user_path: Option<PathBuf>,
+    marker: std::marker::PhantomData<A>
+}
 
 impl <A: ConfigActions>Default for ConfigActionsPlugin<A> {
     fn default() -> ConfigActionsPlugin<A> {
-        ConfigActionsPlugin(Default::default())
+        ConfigActionsPlugin {
+            user_path: None,
+            marker: Default::default()
+        }
+    }
+}
+
+impl <A: ConfigActions>ConfigActionsPlugin<A> {
+    pub fn with_user_config(path: impl AsRef<Path>) -> ConfigActionsPlugin<A> {
+        ConfigActionsPlugin {
+            user_path: user_config_path(path),
+            marker: Default::default()
+        }
     }
 }
 
 impl <A: ConfigActions>Plugin for ConfigActionsPlugin<A> {
     fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<InputHistory>() {
+            app.init_resource::<InputHistory>()
+                .init_resource::<ConsumedInput>()
+                .init_resource::<GamepadAssignments>()
+                .init_resource::<MouseInput>()
+                .init_resource::<PointerInput>()
+                .init_resource::<VirtualCursor>()
+                .add_event::<GamepadAssignmentEvent>()
+                .add_system_to_stage(CoreStage::First, clear_consumed_input)
+                .add_system_to_stage(
+                    CoreStage::PreUpdate,
+                    assign_gamepads.label(ActionsSystem::AssignGamepads).after(InputSystem)
+                )
+                .add_system(read_mouse_input.label(ActionsSystem::ReadMouse))
+                .add_system(
+                    move_virtual_cursor
+                        .label(ActionsSystem::MoveCursor)
+                        .after(ActionsSystem::ConsumeInput)
+                        .after(ActionsSystem::Rebind)
+                        .after(ActionsSystem::ReadMouse)
+                )
+                .add_system(
+                    update_pointers
+                        .label(ActionsSystem::UpdatePointers)
+                        .after(ActionsSystem::MoveCursor)
+                )
+                .add_system(
+                    record_input_history
+                        .label(ActionsSystem::RecordHistory)
+                        .after(ActionsSystem::ConsumeInput)
+                        .after(ActionsSystem::Rebind)
+                        .after(ActionsSystem::ReadMouse)
+                );
+        }
         app.add_asset::<ActionsConfig<A>>()
-            .add_asset_loader(ConfigActionsLoader::<A>::default())
+            .add_asset_loader(ConfigActionsLoader::<A>::new(self.user_path.clone()))
+            .insert_resource(UserBindings::<A>::new(self.user_path.clone()))
             .init_resource::<Actions<A>>()
+            .add_event::<ActionsConfigChanged<A>>()
+            .init_resource::<ActionsRebind<A>>()
+            .init_resource::<ActionContexts<A>>()
+            .add_event::<RebindEvent<A>>()
             .add_startup_system(initialize::<A>)
-            .add_system(handle_keyboard_button_input::<A>)
-            .add_system(handle_mouse_button_input::<A>)
-            .add_system(handle_mouse_axis_input::<A>)
-            .add_system(handle_gamepad_button_input::<A>)
-            .add_system(handle_gamepad_axis_input::<A>);
+            .add_system_to_stage(CoreStage::PreUpdate, reload_config::<A>.label(ActionsSystem::ReloadConfig))
+            .add_system(
+                resolve_actions::<A>
+                    .label(ActionsSystem::HandleInput)
+                    .after(ActionsSystem::RecordHistory)
+                    .after(ActionsSystem::UpdatePointers)
+                    .after(ActionsSystem::Rebind)
+            )
+            .add_system(
+                handle_rebind::<A>
+                    .label(ActionsSystem::Rebind)
+                    .after(ActionsSystem::ConsumeInput)
+            )
+            .add_system(
+                update_action_states::<A>
+                    .label(ActionsSystem::UpdateState)
+                    .after(ActionsSystem::HandleInput)
+            );
+    }
+}
+
+pub struct ConsumeUiInputPlugin;
+
+impl Plugin for ConsumeUiInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConsumedInput>()
+            .add_system(consume_ui_input.label(ActionsSystem::ConsumeInput));
     }
 }
 
@@ -33,206 +131,697 @@ fn initialize<A: ConfigActions>(
     res.handle = assets.load(A::PATH);
 }
 
-fn handle_keyboard_button_input<A: ConfigActions>(
-    input: Res<Input<KeyCode>>,
-    mut actions: ResMut<Actions<A>>,
-    configs: Res<Assets<ActionsConfig<A>>>,
-
+fn reload_config<A: ConfigActions>(
+    mut events: EventReader<AssetEvent<ActionsConfig<A>>>,
+    mut changed: EventWriter<ActionsConfigChanged<A>>,
+    mut actions: ResMut<Actions<A>>
 ) {
-    if let Some(config) = configs.get(actions.handle.clone()) {
-        for (event, action) in config.data.iter() {
-            match event {
-                Event::JustPressed(btn) => match btn {
-                    Button::Keyboard(code) => {
-                        if input.just_pressed(*code) {
-                            actions.data.insert(*action, None);
-                        } else {
-                            if actions.data.contains_key(action) {
-                                actions.data.remove(action);
-                            }
-                        }
-                    },
-                    _ => {}
-                },
-                Event::Pressed(btn) => match btn {
-                    Button::Keyboard(code) => {
-                        if input.pressed(*code) {
-                            actions.data.insert(*action, None);
-                        } else {
-                            if actions.data.contains_key(action) {
-                                actions.data.remove(action);
-                            }
-                        }
-                    },
-                    _ => {}
-                },
-                Event::Value(btn) => match btn {
-                    Button::Keyboard(code) => {
-                        if input.pressed(*code) {
-                            actions.data.insert(*action, Some(1.0));
-                        } else {
-                            if actions.data.contains_key(action) {
-                                actions.data.remove(action);
-                            }
-                        }
-                    },
-                    _ => {}
-                },
-                _ => {}
-            }
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                if *handle == actions.handle {
+                    actions.data.clear();
+                    actions.axis_pairs.clear();
+                    actions.pointers.clear();
+                    actions.drags.clear();
+                    changed.send(ActionsConfigChanged { handle: handle.clone() });
+                }
+            },
+            _ => {}
         }
     }
 }
 
-fn handle_mouse_button_input<A: ConfigActions>(
-    input: Res<Input<MouseButton>>,
-    mut actions: ResMut<Actions<A>>,
-    configs: Res<Assets<ActionsConfig<A>>>
+fn clear_consumed_input(mut consumed: ResMut<ConsumedInput>) {
+    consumed.clear();
+}
+
+fn consume_ui_input(
+    mouse: Res<Input<MouseButton>>,
+    interactions: Query<&Interaction>,
+    mut consumed: ResMut<ConsumedInput>
 ) {
-    if let Some(config) = configs.get(actions.handle.clone()) {
-        for (event, action) in config.data.iter() {
-            match event {
-                Event::JustPressed(btn) => match btn {
-                    Button::Mouse(btn) => {
-                        if input.just_pressed(*btn) {
-                            actions.data.insert(*action, None);
-                        } else {
-                            if actions.data.contains_key(action) {
-                                actions.data.remove(action);
-                            }
-                        }
-                    },
-                    _ => {}
-                },
-                Event::Pressed(btn) => match btn {
-                    Button::Mouse(btn) => {
-                        if input.pressed(*btn) {
-                            actions.data.insert(*action, None);
-                        } else {
-                            if actions.data.contains_key(action) {
-                                actions.data.remove(action);
-                            }
-                        }
-                    },
-                    _ => {}
-                },
-                Event::Value(btn) => match btn {
-                    Button::Mouse(code) => {
-                        if input.pressed(*code) {
-                            actions.data.insert(*action, Some(1.0));
-                        } else {
-                            if actions.data.contains_key(action) {
-                                actions.data.remove(action);
-                            }
-                        }
-                    },
-                    _ => {}
-                },
-                _ => {}
-            }
+    if interactions.iter().any(|interaction| *interaction != Interaction::None) {
+        for btn in mouse.get_pressed().chain(mouse.get_just_released()) {
+            consumed.consume(Button::Mouse(*btn));
         }
     }
 }
 
-fn handle_mouse_axis_input<A: ConfigActions>(
-    mut actions: ResMut<Actions<A>>,
-    mut input: EventReader<MouseMotion>,
-    mut configs: ResMut<Assets<ActionsConfig<A>>>
+fn assign_gamepads(
+    gamepad: Res<Input<GamepadButton>>,
+    mut events: EventReader<GamepadEvent>,
+    mut assignments: ResMut<GamepadAssignments>,
+    mut output: EventWriter<GamepadAssignmentEvent>
 ) {
-    if let Some(config) = configs.get_mut(actions.handle.clone()) {
-        for MouseMotion { delta } in input.iter() {
-            if let Some(action) = config.data.get(&Event::Axis(Axis::Mouse(MouseAxis::X))) {
-                if delta.x == 0.0 {
-                    if actions.data.contains_key(action) {
-                        actions.data.remove(action);
-                    }
-                } else {
-                    actions.data.insert(*action, Some(delta.x));
+    for GamepadEvent(pad, event) in events.iter() {
+        match event {
+            GamepadEventType::Connected => {
+                if !assignments.connected(pad.0) {
+                    assignments.connected.push(pad.0);
                 }
-            }
-            if let Some(action) = config.data.get(&Event::Axis(Axis::Mouse(MouseAxis::Y))) {
-                if delta.y == 0.0 {
-                    if actions.data.contains_key(action) {
-                        actions.data.remove(action);
+                if let Some(player) = assignments.player(pad.0) {
+                    output.send(GamepadAssignmentEvent::Reconnected { player, gamepad: pad.0 });
+                } else if assignments.join.is_none() {
+                    if let Some(player) = assignments.join(pad.0) {
+                        output.send(GamepadAssignmentEvent::Joined { player, gamepad: pad.0 });
                     }
-                } else {
-                    actions.data.insert(*action, Some(delta.y));
                 }
+            },
+            GamepadEventType::Disconnected => {
+                assignments.connected.retain(|x| *x != pad.0);
+                if let Some(player) = assignments.player(pad.0) {
+                    output.send(GamepadAssignmentEvent::Disconnected { player, gamepad: pad.0 });
+                }
+            },
+            _ => {}
+        }
+    }
+    if let Some(join) = assignments.join {
+        let pressed: Vec<usize> = gamepad.get_just_pressed()
+            .filter_map(|btn| (btn.1 == join).then(|| btn.0.0))
+            .collect();
+        for pad in pressed.into_iter() {
+            if let Some(player) = assignments.join(pad) {
+                output.send(GamepadAssignmentEvent::Joined { player, gamepad: pad });
+            }
+        }
+    }
+}
+
+fn read_mouse_input(
+    mut motion: EventReader<MouseMotion>,
+    mut wheel: EventReader<MouseWheel>,
+    mut mouse: ResMut<MouseInput>
+) {
+    mouse.push_motion(motion.iter().fold(Vec2::ZERO, |acc, x| acc + x.delta));
+    mouse.push_scroll(wheel.iter());
+}
+
+fn move_virtual_cursor(
+    time: Res<Time>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepad: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<InputAxis<GamepadAxis>>,
+    gamepad_buttons: Res<InputAxis<GamepadButton>>,
+    history: Res<InputHistory>,
+    consumed: Res<ConsumedInput>,
+    gamepads: Res<GamepadAssignments>,
+    mouse_input: Res<MouseInput>,
+    pointers: Res<PointerInput>,
+    windows: Res<Windows>,
+    mut cursor: ResMut<VirtualCursor>
+) {
+    let window = match windows.get_primary() {
+        Some(window) if cursor.enabled => window,
+        _ => {
+            cursor.position = None;
+            return;
+        }
+    };
+    let input = InputSources {
+        keyboard: &keyboard,
+        mouse: &mouse,
+        gamepad: &gamepad,
+        gamepad_axes: &gamepad_axes,
+        gamepad_buttons: &gamepad_buttons,
+        mouse_input: &mouse_input,
+        pointers: &pointers,
+        history: &history,
+        consumed: &consumed,
+        gamepads: &gamepads,
+        player: 0,
+        now: time.time_since_startup()
+    };
+    let event = Event::DualAxis { input: cursor.input.clone(), settings: cursor.settings };
+    let delta = input.evaluate_dual(&event).unwrap_or(Vec2::ZERO) * cursor.speed * time.delta_seconds();
+    let size = Vec2::new(window.width(), window.height());
+    let position = cursor.position.unwrap_or(size / 2.0) + delta;
+    cursor.position = Some(position.clamp(Vec2::ZERO, size));
+}
+
+fn update_pointers(
+    windows: Res<Windows>,
+    mouse: Res<Input<MouseButton>>,
+    gamepad: Res<Input<GamepadButton>>,
+    cursor: Res<VirtualCursor>,
+    cameras: Query<(&Camera, &GlobalTransform), With<PointerCamera>>,
+    mut pointers: ResMut<PointerInput>
+) {
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => {
+            *pointers = Default::default();
+            return;
+        }
+    };
+    let size = Vec2::new(window.width(), window.height());
+    let camera = cameras.iter().find(|(camera, _)| camera.window == window.id());
+    let position = |window: Vec2| {
+        let normalized = window / size;
+        PointerPosition {
+            window,
+            normalized,
+            ray: camera.map(|(camera, transform)| {
+                let ndc = normalized * 2.0 - Vec2::ONE;
+                let ndc_to_world = transform.compute_matrix() * camera.projection_matrix.inverse();
+                let near = ndc_to_world.project_point3(ndc.extend(1.0));
+                let far = ndc_to_world.project_point3(ndc.extend(0.5));
+                PointerRay { origin: near, direction: (far - near).normalize() }
+            })
+        }
+    };
+    pointers.cursor = window.cursor_position().map(&position);
+    pointers.virtual_cursor = cursor.position.map(&position);
+
+    let pressed: Vec<Button> = mouse.get_pressed().map(|btn| Button::Mouse(*btn))
+        .chain(gamepad.get_pressed().map(|btn| Button::Gamepad(btn.0.0, btn.1)))
+        .collect();
+    pointers.drags.retain(|(button, _), _| pressed.contains(button));
+    for pointer in [Pointer::Cursor, Pointer::Virtual] {
+        let position = match pointers.get(pointer) {
+            Some(position) => position.window,
+            None => continue
+        };
+        for button in pressed.iter() {
+            pointers.drags.entry((button.clone(), pointer))
+                .or_insert_with(|| DragState::new(position))
+                .update(position);
+        }
+    }
+}
+
+fn record_input_history(
+    time: Res<Time>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepad: Res<Input<GamepadButton>>,
+    mouse_input: Res<MouseInput>,
+    consumed: Res<ConsumedInput>,
+    mut history: ResMut<InputHistory>
+) {
+    let mut frame = InputFrame {
+        time: time.time_since_startup(),
+        ..Default::default()
+    };
+    frame.just_pressed.extend(keyboard.get_just_pressed().map(|code| Button::Keyboard(*code)));
+    frame.just_pressed.extend(mouse.get_just_pressed().map(|btn| Button::Mouse(*btn)));
+    frame.just_pressed.extend(gamepad.get_just_pressed().map(|btn| Button::Gamepad(btn.0.0, btn.1)));
+    frame.just_released.extend(keyboard.get_just_released().map(|code| Button::Keyboard(*code)));
+    frame.just_released.extend(mouse.get_just_released().map(|btn| Button::Mouse(*btn)));
+    frame.just_released.extend(gamepad.get_just_released().map(|btn| Button::Gamepad(btn.0.0, btn.1)));
+    frame.just_pressed.extend(mouse_input.scrolled_directions().into_iter().map(Button::Wheel));
+    frame.just_released.extend(mouse_input.stopped_directions().into_iter().map(Button::Wheel));
+    frame.just_pressed.retain(|btn| !consumed.is_consumed(btn));
+    frame.just_released.retain(|btn| !consumed.is_consumed(btn));
+    if frame.just_pressed.is_empty() && frame.just_released.is_empty() {
+        return;
+    }
+    frame.pressed.extend(keyboard.get_pressed().map(|code| Button::Keyboard(*code)));
+    frame.pressed.extend(mouse.get_pressed().map(|btn| Button::Mouse(*btn)));
+    frame.pressed.extend(gamepad.get_pressed().map(|btn| Button::Gamepad(btn.0.0, btn.1)));
+    frame.pressed.extend(mouse_input.scrolled_directions().into_iter().map(Button::Wheel));
+    frame.pressed.retain(|btn| !consumed.is_consumed(btn));
+    history.push(frame);
+}
+
+fn resolve<A: ConfigActions>(
+    bindings: &HashMap<Event, Vec<A>>,
+    combine: impl Fn(A) -> CombinePolicy,
+    input: &InputSources,
+    contexts: &ActionContexts<A>,
+    state: &mut ActionState<A>
+) {
+    let active: Vec<(&Event, A, f32, i32)> = bindings.iter()
+        .filter_map(|(event, actions)| input.evaluate(event).map(|value| (event, actions, value)))
+        .flat_map(|(event, actions, value)| actions.iter().map(move |action| (event, *action, value)))
+        .filter_map(|(event, action, value)| {
+            contexts.priority(action).map(|priority| (event, action, value, priority))
+        })
+        .collect();
+
+    let chords: Vec<&Vec<Button>> = active.iter()
+        .filter_map(|(event, _, _, _)| match event {
+            Event::Chord(buttons) => Some(buttons),
+            _ => None
+        })
+        .collect();
+    let covered = |buttons: &[Button]| chords.iter()
+        .any(|chord| chord.len() > buttons.len() && buttons.iter().all(|btn| chord.contains(btn)));
+
+    let candidates: Vec<(&Event, A, f32, i32)> = active.iter()
+        .filter(|(event, _, _, _)| match event {
+            Event::Chord(buttons) => !covered(buttons),
+            _ => !event.buttons().into_iter().any(|btn| covered(std::slice::from_ref(btn)))
+        })
+        .copied()
+        .collect();
+    let mut priorities: Vec<i32> = bindings.values()
+        .flatten()
+        .filter_map(|action| contexts.priority(*action))
+        .collect();
+    priorities.sort_unstable_by(|a, b| b.cmp(a));
+    priorities.dedup();
+
+    let mut consumed_events: HashSet<&Event> = HashSet::default();
+    let mut consumed_buttons: HashSet<&Button> = HashSet::default();
+    let mut consumed_axes: HashSet<&Axis> = HashSet::default();
+    let mut data: HashMap<A, Option<f32>> = HashMap::default();
+    let mut pairs: HashMap<A, Vec2> = HashMap::default();
+    let mut pointers: HashMap<A, PointerPosition> = HashMap::default();
+    let mut drags: HashMap<A, Drag> = HashMap::default();
+    for priority in priorities.into_iter() {
+        let level: Vec<&(&Event, A, f32, i32)> = candidates.iter()
+            .filter(|(event, _, _, x)| *x == priority && !consumed_events.contains(event)
+                && !event.buttons().iter().any(|btn| consumed_buttons.contains(btn))
+                && !event.axes().iter().any(|axis| consumed_axes.contains(axis)))
+            .collect();
+        for (event, action, value, _) in level.into_iter() {
+            let entry = data.entry(*action).or_insert(None);
+            if let Some(drag) = input.evaluate_drag(event) {
+                drags.insert(*action, drag);
+            } else if let Some(position) = input.evaluate_pointer(event) {
+                pointers.insert(*action, position);
+            } else if let Some(value) = input.evaluate_dual(event) {
+                let pair = match pairs.get(action) {
+                    Some(current) => combine(*action).combine_pair(*current, value),
+                    None => value
+                };
+                pairs.insert(*action, pair);
+                *entry = Some(pair.length());
+            } else if event.is_analog() {
+                *entry = Some(match *entry {
+                    Some(current) => combine(*action).combine(current, *value),
+                    None => *value
+                });
+            }
+        }
+        for (event, actions) in bindings.iter() {
+            if actions.iter().any(|action| contexts.priority(*action) == Some(priority)) {
+                consumed_events.insert(event);
+                consumed_buttons.extend(event.buttons());
+                consumed_axes.extend(event.axes());
             }
         }
     }
+    for (action, drag) in state.drags.iter() {
+        if !drag.ended && !drags.contains_key(action) && state.data.contains_key(action) {
+            drags.insert(*action, Drag { delta: Vec2::ZERO, ended: true, ..*drag });
+        }
+    }
+    state.data = data;
+    state.axis_pairs = pairs;
+    state.pointers = pointers;
+    state.drags = drags;
 }
 
-fn handle_gamepad_button_input<A: ConfigActions>(
+fn resolve_actions<A: ConfigActions>(
+    time: Res<Time>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepad: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<InputAxis<GamepadAxis>>,
+    gamepad_buttons: Res<InputAxis<GamepadButton>>,
+    history: Res<InputHistory>,
+    consumed: Res<ConsumedInput>,
+    gamepads: Res<GamepadAssignments>,
+    mouse_input: Res<MouseInput>,
+    pointers: Res<PointerInput>,
+    contexts: Res<ActionContexts<A>>,
     mut actions: ResMut<Actions<A>>,
-    input: Res<Input<GamepadButton>>,
+    mut players: Query<(&InputMap<A>, &mut ActionState<A>, Option<&ActionContexts<A>>)>,
     configs: Res<Assets<ActionsConfig<A>>>
 ) {
+    let input = InputSources {
+        keyboard: &keyboard,
+        mouse: &mouse,
+        gamepad: &gamepad,
+        gamepad_axes: &gamepad_axes,
+        gamepad_buttons: &gamepad_buttons,
+        mouse_input: &mouse_input,
+        pointers: &pointers,
+        history: &history,
+        consumed: &consumed,
+        gamepads: &gamepads,
+        player: 0,
+        now: time.time_since_startup()
+    };
     if let Some(config) = configs.get(actions.handle.clone()) {
-        for (event, action) in config.data.iter() {
-            match event {
-                Event::JustPressed(btn) => match btn {
-                    Button::Gamepad(id, btn) => {
-                        if input.just_pressed(GamepadButton(Gamepad(*id), *btn)) {
-                            actions.data.insert(*action, None);
-                        } else {
-                            if actions.data.contains_key(action) {
-                                actions.data.remove(action);
-                            }
-                        }
-                    },
-                    _ => {}
-                },
-                Event::Pressed(btn) => match btn {
-                    Button::Gamepad(id, btn) => {
-                        if input.pressed(GamepadButton(Gamepad(*id), *btn)) {
-                            actions.data.insert(*action, None);
-                        } else {
-                            if actions.data.contains_key(action) {
-                                actions.data.remove(action);
-                            }
-                        }
-                    },
-                    _ => {}
-                },
-                _ => {}
+        resolve(&config.data, |action| config.combine_policy(action), &input, &contexts, &mut actions);
+    }
+    for (map, mut state, player_contexts) in players.iter_mut() {
+        let input = InputSources { player: map.player, ..input };
+        let contexts = player_contexts.unwrap_or(&*contexts);
+        resolve(&map.data, |action| map.combine_policy(action), &input, contexts, &mut state);
+    }
+}
+
+fn handle_rebind<A: ConfigActions>(
+    time: Res<Time>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepad: Res<Input<GamepadButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut gamepad_events: EventReader<GamepadEvent>,
+    mut rebind: ResMut<ActionsRebind<A>>,
+    mut events: EventWriter<RebindEvent<A>>,
+    gamepads: Res<GamepadAssignments>,
+    mut consumed: ResMut<ConsumedInput>,
+    actions: Res<Actions<A>>,
+    mut configs: ResMut<Assets<ActionsConfig<A>>>
+) {
+    let now = time.time_since_startup();
+    let delta = motion.iter().fold(Vec2::ZERO, |acc, x| acc + x.delta);
+    let axis_threshold = rebind.axis_threshold;
+    let gamepad_axis = gamepad_events.iter()
+        .filter_map(|GamepadEvent(pad, event)| match event {
+            GamepadEventType::AxisChanged(axis, value) if value.abs() >= axis_threshold => {
+                Some(Axis::Gamepad(pad.0, *axis))
+            },
+            _ => None
+        })
+        .filter(|axis| !consumed.is_axis_consumed(axis))
+        .last();
+    let mouse_axis = if delta.x.abs() >= rebind.mouse_threshold {
+        Some(Axis::Mouse(MouseAxis::X))
+    } else if delta.y.abs() >= rebind.mouse_threshold {
+        Some(Axis::Mouse(MouseAxis::Y))
+    } else {
+        None
+    };
+    let axis = mouse_axis
+        .filter(|axis| !consumed.is_axis_consumed(axis))
+        .or(gamepad_axis)
+        .map(|axis| Event::Axis(match axis {
+            Axis::Gamepad(id, axis) if gamepads.player(id).is_some() => Axis::PlayerGamepad(axis),
+            _ => axis
+        }));
+    let button = keyboard.get_just_pressed().map(|code| Button::Keyboard(*code))
+        .chain(mouse.get_just_pressed().map(|btn| Button::Mouse(*btn)))
+        .chain(gamepad.get_just_pressed().map(|btn| Button::Gamepad(btn.0.0, btn.1)))
+        .find(|button| !consumed.is_consumed(button))
+        .map(|button| match button {
+            Button::Gamepad(id, btn) if gamepads.player(id).is_some() => Button::PlayerGamepad(btn),
+            _ => button
+        });
+
+    if rebind.capture.is_some() {
+        for device in [Device::Keyboard, Device::Mouse, Device::Gamepad] {
+            consumed.consume_device(device);
+        }
+    }
+    let (action, kind, started_at) = match rebind.capture.as_mut() {
+        None => return,
+        Some(capture) => match capture.started_at {
+            Some(at) => (capture.action, capture.kind, at),
+            None => {
+                capture.started_at = Some(now);
+                return;
             }
         }
+    };
+    if rebind.timeout.map(|timeout| now.saturating_sub(started_at) >= timeout).unwrap_or(false) {
+        rebind.capture = None;
+        events.send(RebindEvent::TimedOut(action));
+        return;
+    }
+
+    let captured = match kind {
+        CaptureKind::Button => button.map(Event::Pressed),
+        CaptureKind::Axis => axis,
+        CaptureKind::Any => button.map(Event::Pressed).or(axis)
+    };
+    if let Some(event) = captured {
+        rebind.capture = None;
+        let conflicts = configs.get(actions.handle.clone())
+            .map(|config| config.conflicts(&event, action))
+            .unwrap_or_default();
+        if rebind.apply {
+            if let Some(config) = configs.get_mut(actions.handle.clone()) {
+                if rebind.replace {
+                    config.unbind_action(action);
+                }
+                config.bind(event.clone(), action);
+            }
+        }
+        events.send(RebindEvent::Captured { action, event, conflicts });
     }
 }
 
-fn handle_gamepad_axis_input<A: ConfigActions>(
+fn update_action_states<A: ConfigActions>(
+    time: Res<Time>,
     mut actions: ResMut<Actions<A>>,
-    mut input: EventReader<GamepadEvent>,
-    configs: Res<Assets<ActionsConfig<A>>>
+    mut players: Query<&mut ActionState<A>>
 ) {
-    if let Some(config) = configs.get(actions.handle.clone()) {
-        for GamepadEvent(gamepad, event) in input.iter() {
-            match event {
-                GamepadEventType::AxisChanged(axis, value) => {
-                    if let Some(action) = config.data.get(&Event::Axis(Axis::Gamepad(gamepad.0, *axis))) {
-                        if *value == 0.0 {
-                            if actions.data.contains_key(action) {
-                                actions.data.remove(action);
-                            }
-                        } else {
-                            actions.data.insert(*action, Some(*value));
-                        }
-                    }
+    let now = time.time_since_startup();
+    actions.update(now);
+    for mut state in players.iter_mut() {
+        state.update(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::*;
+    use crate::tests::{TestActions, TestInput};
+
+    impl TestInput {
+        fn resolve(
+            &self,
+            bindings: &HashMap<Event, Vec<TestActions>>,
+            contexts: &ActionContexts<TestActions>,
+            state: &mut ActionState<TestActions>
+        ) {
+            resolve(bindings, |_| CombinePolicy::default(), &self.sources(), contexts, state);
+        }
+    }
+
+    #[test]
+    fn higher_context_consumes_held_buttons() {
+        let mut bindings = HashMap::default();
+        bindings.insert(Event::JustPressed(Button::Keyboard(KeyCode::Space)), vec![TestActions::Confirm]);
+        bindings.insert(Event::Pressed(Button::Keyboard(KeyCode::Space)), vec![TestActions::Jump]);
+        let mut contexts = ActionContexts::default();
+        contexts
+            .add("gameplay", 0, [TestActions::Jump])
+            .add("dialog", 10, [TestActions::Confirm]);
+        let mut input = TestInput::default();
+        let mut state = ActionState::default();
+
+        input.keyboard.press(KeyCode::Space);
+        input.resolve(&bindings, &contexts, &mut state);
+        assert!(state.action(TestActions::Confirm));
+        assert!(!state.action(TestActions::Jump));
+
+        input.keyboard.clear();
+        input.resolve(&bindings, &contexts, &mut state);
+        assert!(!state.action(TestActions::Confirm));
+        assert!(!state.action(TestActions::Jump));
+
+        contexts.disable("dialog");
+        input.resolve(&bindings, &contexts, &mut state);
+        assert!(state.action(TestActions::Jump));
+    }
+
+    #[test]
+    fn higher_context_consumes_axes() {
+        let axis = Axis::Gamepad(0, GamepadAxisType::LeftStickX);
+        let mut bindings = HashMap::default();
+        bindings.insert(Event::Axis(axis.clone()), vec![TestActions::Confirm]);
+        bindings.insert(
+            Event::Processed { event: Box::new(Event::Axis(axis.clone())), settings: Default::default() },
+            vec![TestActions::Jump]
+        );
+        bindings.insert(
+            Event::DualAxis {
+                input: crate::DualAxis::Axes { x: axis, y: Axis::Gamepad(0, GamepadAxisType::LeftStickY) },
+                settings: Default::default()
+            },
+            vec![TestActions::Fire]
+        );
+        let mut contexts = ActionContexts::default();
+        contexts
+            .add("gameplay", 0, [TestActions::Jump, TestActions::Fire])
+            .add("menu", 10, [TestActions::Confirm]);
+        let mut input = TestInput::default();
+        let mut state = ActionState::default();
+
+        input.gamepad_axes.set(GamepadAxis(Gamepad(0), GamepadAxisType::LeftStickX), 0.8);
+        input.resolve(&bindings, &contexts, &mut state);
+        assert_eq!(state.value(TestActions::Confirm), Some(0.8));
+        assert!(!state.action(TestActions::Jump));
+        assert!(!state.action(TestActions::Fire));
+    }
+
+    #[test]
+    fn disabled_chord_does_not_cover_buttons() {
+        let mut bindings = HashMap::default();
+        bindings.insert(
+            Event::Chord(vec![Button::Keyboard(KeyCode::LControl), Button::Keyboard(KeyCode::S)]),
+            vec![TestActions::Confirm]
+        );
+        bindings.insert(Event::Pressed(Button::Keyboard(KeyCode::S)), vec![TestActions::Jump]);
+        let mut contexts = ActionContexts::default();
+        contexts
+            .add("gameplay", 0, [TestActions::Jump])
+            .add("editor", 10, [TestActions::Confirm]);
+        contexts.disable("editor");
+        let mut input = TestInput::default();
+        let mut state = ActionState::default();
+
+        input.keyboard.press(KeyCode::LControl);
+        input.keyboard.press(KeyCode::S);
+        input.resolve(&bindings, &contexts, &mut state);
+        assert!(state.action(TestActions::Jump));
+        assert!(!state.action(TestActions::Confirm));
+    }
+
+    #[test]
+    fn chord_covers_virtual_axis() {
+        let mut bindings = HashMap::default();
+        bindings.insert(
+            Event::Chord(vec![Button::Keyboard(KeyCode::LControl), Button::Keyboard(KeyCode::D)]),
+            vec![TestActions::Confirm]
+        );
+        bindings.insert(
+            Event::VirtualAxis {
+                negative: Button::Keyboard(KeyCode::A),
+                positive: Button::Keyboard(KeyCode::D),
+                both: Default::default()
+            },
+            vec![TestActions::Jump]
+        );
+        let contexts = ActionContexts::default();
+        let mut input = TestInput::default();
+        let mut state = ActionState::default();
+
+        input.keyboard.press(KeyCode::D);
+        input.resolve(&bindings, &contexts, &mut state);
+        assert_eq!(state.value(TestActions::Jump), Some(1.0));
+
+        input.keyboard.press(KeyCode::LControl);
+        input.resolve(&bindings, &contexts, &mut state);
+        assert!(state.action(TestActions::Confirm));
+        assert!(!state.action(TestActions::Jump));
+    }
+
+    #[test]
+    fn chord_covers_dual_axis_buttons() {
+        let mut bindings = HashMap::default();
+        bindings.insert(
+            Event::Chord(vec![Button::Keyboard(KeyCode::LControl), Button::Keyboard(KeyCode::S)]),
+            vec![TestActions::Confirm]
+        );
+        bindings.insert(
+            Event::DualAxis {
+                input: crate::DualAxis::Buttons {
+                    up: Button::Keyboard(KeyCode::W),
+                    down: Button::Keyboard(KeyCode::S),
+                    left: Button::Keyboard(KeyCode::A),
+                    right: Button::Keyboard(KeyCode::D)
                 },
-                GamepadEventType::ButtonChanged(btn, value) => {
-                    if let Some(action) = config.data.get(&Event::Value(Button::Gamepad(gamepad.0, *btn))) {
-                        if *value == 0.0 {
-                            if actions.data.contains_key(action) {
-                                actions.data.remove(action);
-                            }
-                        } else {
-                            actions.data.insert(*action, Some(*value));
-                        }
-                    }
-                }
-                _ => {}
+                settings: Default::default()
+            },
+            vec![TestActions::Fire]
+        );
+        let contexts = ActionContexts::default();
+        let mut input = TestInput::default();
+        let mut state = ActionState::default();
+
+        input.keyboard.press(KeyCode::S);
+        input.resolve(&bindings, &contexts, &mut state);
+        assert_eq!(state.axis_pair(TestActions::Fire), Some(Vec2::new(0.0, -1.0)));
+
+        input.keyboard.press(KeyCode::LControl);
+        input.resolve(&bindings, &contexts, &mut state);
+        assert!(state.action(TestActions::Confirm));
+        assert!(!state.action(TestActions::Fire));
+        assert_eq!(state.axis_pair(TestActions::Fire), None);
+    }
+
+    fn virtual_axis(both: crate::BothHeld, frames: &[(u64, &[KeyCode])]) -> Option<f32> {
+        let mut bindings = HashMap::default();
+        bindings.insert(
+            Event::VirtualAxis { negative: Button::Keyboard(KeyCode::A), positive: Button::Keyboard(KeyCode::D), both },
+            vec![TestActions::Jump]
+        );
+        let mut input = TestInput::default();
+        for (ms, keys) in frames.iter() {
+            input.now = Duration::from_millis(*ms);
+            for key in keys.iter() {
+                input.keyboard.press(*key);
             }
+            input.history.push(InputFrame {
+                time: input.now,
+                just_pressed: keys.iter().map(|key| Button::Keyboard(*key)).collect(),
+                ..Default::default()
+            });
         }
+        let mut state = ActionState::default();
+        input.resolve(&bindings, &ActionContexts::default(), &mut state);
+        state.value(TestActions::Jump)
+    }
+
+    #[test]
+    fn virtual_axis_single_button() {
+        assert_eq!(virtual_axis(crate::BothHeld::Cancel, &[(0, &[KeyCode::A])]), Some(-1.0));
+        assert_eq!(virtual_axis(crate::BothHeld::Cancel, &[(0, &[KeyCode::D])]), Some(1.0));
+    }
+
+    #[test]
+    fn virtual_axis_both_held_cancel() {
+        assert_eq!(virtual_axis(crate::BothHeld::Cancel, &[(0, &[KeyCode::A]), (100, &[KeyCode::D])]), None);
+        assert_eq!(virtual_axis(crate::BothHeld::Cancel, &[(0, &[KeyCode::A, KeyCode::D])]), None);
+    }
+
+    #[test]
+    fn virtual_axis_both_held_last_pressed() {
+        let both = crate::BothHeld::LastPressed;
+        assert_eq!(virtual_axis(both, &[(0, &[KeyCode::A]), (100, &[KeyCode::D])]), Some(1.0));
+        assert_eq!(virtual_axis(both, &[(0, &[KeyCode::D]), (100, &[KeyCode::A])]), Some(-1.0));
+        assert_eq!(virtual_axis(both, &[(0, &[KeyCode::A, KeyCode::D])]), Some(1.0));
+    }
+
+    #[test]
+    fn virtual_axis_both_held_first_pressed() {
+        let both = crate::BothHeld::FirstPressed;
+        assert_eq!(virtual_axis(both, &[(0, &[KeyCode::A]), (100, &[KeyCode::D])]), Some(-1.0));
+        assert_eq!(virtual_axis(both, &[(0, &[KeyCode::D]), (100, &[KeyCode::A])]), Some(1.0));
+        assert_eq!(virtual_axis(both, &[(0, &[KeyCode::A, KeyCode::D])]), Some(-1.0));
+    }
+
+    #[test]
+    fn released_drag_ends_for_one_frame() {
+        let button = Button::Mouse(MouseButton::Left);
+        let mut bindings = HashMap::default();
+        bindings.insert(
+            Event::Drag { button: button.clone(), pointer: Pointer::Cursor, threshold_px: 0 },
+            vec![TestActions::Fire]
+        );
+        let contexts = ActionContexts::default();
+        let mut input = TestInput::default();
+        let mut state = ActionState::default();
+
+        input.mouse.press(MouseButton::Left);
+        let mut drag = DragState::new(Vec2::ZERO);
+        drag.update(Vec2::new(10.0, 0.0));
+        input.pointers.drags.insert((button.clone(), Pointer::Cursor), drag);
+        input.resolve(&bindings, &contexts, &mut state);
+        assert!(state.action(TestActions::Fire));
+        assert_eq!(state.drag(TestActions::Fire).map(|drag| drag.ended), Some(false));
+
+        input.mouse.release(MouseButton::Left);
+        input.pointers.drags.clear();
+        input.resolve(&bindings, &contexts, &mut state);
+        assert!(!state.action(TestActions::Fire));
+        let ended = state.drag(TestActions::Fire).unwrap();
+        assert!(ended.ended);
+        assert_eq!(ended.delta, Vec2::ZERO);
+        assert_eq!(ended.displacement(), Vec2::new(10.0, 0.0));
+
+        input.resolve(&bindings, &contexts, &mut state);
+        assert_eq!(state.drag(TestActions::Fire), None);
     }
 }
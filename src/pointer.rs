@@ -0,0 +1,191 @@
+use bevy::ecs::component::Component;
+use bevy::math::{Vec2, Vec3};
+use bevy::utils::HashMap;
+use bevy::input::gamepad::GamepadAxisType;
+use serde::{ Serialize, Deserialize };
+
+use crate::{Axis, Button, DualAxis, DualAxisProcessing};
+
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, Serialize, Deserialize)]
+pub enum Pointer {
+    Cursor,
+    Virtual
+}
+
+impl Default for Pointer {
+    fn default() -> Pointer {
+        Pointer::Cursor
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PointerRay {
+    pub origin: Vec3,
+    pub direction: Vec3
+}
+
+impl PointerRay {
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    pub fn intersect_plane(&self, point: Vec3, normal: Vec3) -> Option<Vec3> {
+        let denominator = self.direction.dot(normal);
+        if denominator.abs() <= f32::EPSILON {
+            return None;
+        }
+        let distance = (point - self.origin).dot(normal) / denominator;
+        (distance >= 0.0).then(|| self.at(distance))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PointerPosition {
+    pub window: Vec2,
+    pub normalized: Vec2,
+    pub ray: Option<PointerRay>
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DragState {
+    pub start: Vec2,
+    pub previous: Vec2,
+    pub position: Vec2,
+    pub max_distance: f32
+}
+
+impl DragState {
+    pub(crate) fn new(position: Vec2) -> DragState {
+        DragState {
+            start: position,
+            previous: position,
+            position,
+            max_distance: 0.0
+        }
+    }
+
+    pub(crate) fn update(&mut self, position: Vec2) {
+        self.previous = self.position;
+        self.position = position;
+        self.max_distance = self.max_distance.max(position.distance(self.start));
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Drag {
+    pub start: Vec2,
+    pub position: Vec2,
+    pub delta: Vec2,
+    pub ended: bool
+}
+
+impl Drag {
+    pub fn displacement(&self) -> Vec2 {
+        self.position - self.start
+    }
+}
+
+impl From<&DragState> for Drag {
+    fn from(state: &DragState) -> Drag {
+        Drag {
+            start: state.start,
+            position: state.position,
+            delta: state.position - state.previous,
+            ended: false
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Component)]
+pub struct PointerCamera;
+
+#[derive(Debug, Default)]
+pub struct PointerInput {
+    pub cursor: Option<PointerPosition>,
+    pub virtual_cursor: Option<PointerPosition>,
+    pub drags: HashMap<(Button, Pointer), DragState>
+}
+
+impl PointerInput {
+    pub fn get(&self, pointer: Pointer) -> Option<&PointerPosition> {
+        match pointer {
+            Pointer::Cursor => self.cursor.as_ref(),
+            Pointer::Virtual => self.virtual_cursor.as_ref()
+        }
+    }
+
+    pub fn drag(&self, button: &Button, pointer: Pointer) -> Option<&DragState> {
+        self.drags.get(&(button.clone(), pointer))
+    }
+}
+
+#[derive(Debug)]
+pub struct VirtualCursor {
+    pub enabled: bool,
+    pub position: Option<Vec2>,
+    pub input: DualAxis,
+    pub settings: DualAxisProcessing,
+    pub speed: f32
+}
+
+impl Default for VirtualCursor {
+    fn default() -> VirtualCursor {
+        VirtualCursor {
+            enabled: false,
+            position: None,
+            input: DualAxis::Axes {
+                x: Axis::PlayerGamepad(GamepadAxisType::LeftStickX),
+                y: Axis::PlayerGamepad(GamepadAxisType::LeftStickY)
+            },
+            settings: DualAxisProcessing {
+                dead_zone: 0.1,
+                normalize: true,
+                ..Default::default()
+            },
+            speed: 800.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ray_hits_plane_in_front() {
+        let ray = PointerRay { origin: Vec3::new(1.0, 5.0, 0.0), direction: Vec3::new(0.0, -1.0, 0.0) };
+        assert_eq!(ray.intersect_plane(Vec3::ZERO, Vec3::Y), Some(Vec3::new(1.0, 0.0, 0.0)));
+        assert_eq!(ray.intersect_plane(Vec3::ZERO, -Vec3::Y), Some(Vec3::new(1.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn parallel_ray_misses_plane() {
+        let ray = PointerRay { origin: Vec3::new(0.0, 5.0, 0.0), direction: Vec3::X };
+        assert_eq!(ray.intersect_plane(Vec3::ZERO, Vec3::Y), None);
+        let ray = PointerRay { origin: Vec3::ZERO, direction: Vec3::X };
+        assert_eq!(ray.intersect_plane(Vec3::ZERO, Vec3::Y), None);
+    }
+
+    #[test]
+    fn plane_behind_origin_is_missed() {
+        let ray = PointerRay { origin: Vec3::new(0.0, 5.0, 0.0), direction: Vec3::Y };
+        assert_eq!(ray.intersect_plane(Vec3::ZERO, Vec3::Y), None);
+        let ray = PointerRay { origin: Vec3::ZERO, direction: Vec3::Y };
+        assert_eq!(ray.intersect_plane(Vec3::ZERO, Vec3::Y), Some(Vec3::ZERO));
+    }
+
+    #[test]
+    fn drag_state_keeps_max_distance() {
+        let mut state = DragState::new(Vec2::ZERO);
+        state.update(Vec2::new(3.0, 4.0));
+        assert_eq!(state.max_distance, 5.0);
+        state.update(Vec2::new(1.0, 0.0));
+        assert_eq!(state.max_distance, 5.0);
+        assert_eq!(state.previous, Vec2::new(3.0, 4.0));
+
+        let drag = Drag::from(&state);
+        assert_eq!(drag.delta, Vec2::new(-2.0, -4.0));
+        assert_eq!(drag.displacement(), Vec2::new(1.0, 0.0));
+        assert!(!drag.ended);
+    }
+}
@@ -0,0 +1,316 @@
+use std::hash::{Hash, Hasher};
+use bevy::math::Vec2;
+use serde::{ Serialize, Deserialize };
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ResponseCurve {
+    Linear,
+    Exponential(f32),
+    Smoothstep,
+    Points(Vec<(f32, f32)>)
+}
+
+impl Default for ResponseCurve {
+    fn default() -> ResponseCurve {
+        ResponseCurve::Linear
+    }
+}
+
+impl ResponseCurve {
+    pub fn apply(&self, value: f32) -> f32 {
+        if value == 0.0 {
+            return 0.0;
+        }
+        let x = value.abs();
+        let y = match self {
+            ResponseCurve::Linear => x,
+            ResponseCurve::Exponential(exponent) => x.powf(*exponent),
+            ResponseCurve::Smoothstep => {
+                let x = x.min(1.0);
+                x * x * (3.0 - 2.0 * x)
+            },
+            ResponseCurve::Points(points) => Self::interpolate(points, x)
+        };
+        value.signum() * y
+    }
+
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        match self {
+            ResponseCurve::Exponential(exponent) if !exponent.is_finite() || *exponent <= 0.0 => {
+                Err(anyhow::anyhow!("Response curve exponent must be finite and greater than 0.0, got {}", exponent))
+            },
+            ResponseCurve::Points(points) if !points.windows(2).all(|pair| pair[0].0 <= pair[1].0) => {
+                Err(anyhow::anyhow!("Response curve points must be sorted by input, got {:?}", points))
+            },
+            _ => Ok(())
+        }
+    }
+
+    fn interpolate(points: &[(f32, f32)], x: f32) -> f32 {
+        let (first, last) = match (points.first(), points.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return x
+        };
+        if x <= first.0 {
+            return first.1;
+        }
+        points.windows(2)
+            .find(|pair| x <= pair[1].0)
+            .map(|pair| {
+                let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+                if x1 <= x0 { y1 } else { y0 + (y1 - y0) * (x - x0) / (x1 - x0) }
+            })
+            .unwrap_or(last.1)
+    }
+
+    fn bits(&self) -> (u8, Vec<(u32, u32)>) {
+        match self {
+            ResponseCurve::Linear => (0, vec![]),
+            ResponseCurve::Exponential(exponent) => (1, vec![(exponent.to_bits(), 0)]),
+            ResponseCurve::Smoothstep => (2, vec![]),
+            ResponseCurve::Points(points) => {
+                (3, points.iter().map(|(x, y)| (x.to_bits(), y.to_bits())).collect())
+            }
+        }
+    }
+}
+
+impl PartialEq for ResponseCurve {
+    fn eq(&self, other: &ResponseCurve) -> bool {
+        self.bits() == other.bits()
+    }
+}
+
+impl Eq for ResponseCurve {}
+
+impl Hash for ResponseCurve {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits().hash(state);
+    }
+}
+
+fn rescale(magnitude: f32, dead_zone: f32, outer_dead_zone: Option<f32>) -> f32 {
+    let outer = outer_dead_zone.unwrap_or(1.0);
+    if magnitude <= dead_zone {
+        0.0
+    } else if outer <= dead_zone || outer_dead_zone.map(|outer| magnitude >= outer).unwrap_or(false) {
+        1.0
+    } else {
+        (magnitude - dead_zone) / (outer - dead_zone)
+    }
+}
+
+fn validate_zones(dead_zone: f32, outer_dead_zone: Option<f32>) -> Result<(), anyhow::Error> {
+    if !(0.0..1.0).contains(&dead_zone) {
+        return Err(anyhow::anyhow!("dead_zone must be at least 0.0 and below 1.0, got {}", dead_zone));
+    }
+    match outer_dead_zone {
+        Some(outer) if outer.is_nan() || outer <= dead_zone => Err(anyhow::anyhow!(
+            "outer_dead_zone must be greater than dead_zone ({}), got {}", dead_zone, outer
+        )),
+        _ => Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AxisProcessing {
+    pub dead_zone: f32,
+    pub outer_dead_zone: Option<f32>,
+    pub curve: ResponseCurve,
+    pub sensitivity: f32,
+    pub invert: bool,
+    pub clamp: bool
+}
+
+impl Default for AxisProcessing {
+    fn default() -> AxisProcessing {
+        AxisProcessing {
+            dead_zone: 0.0,
+            outer_dead_zone: None,
+            curve: Default::default(),
+            sensitivity: 1.0,
+            invert: false,
+            clamp: false
+        }
+    }
+}
+
+impl AxisProcessing {
+    pub fn apply(&self, value: f32) -> f32 {
+        let magnitude = rescale(value.abs(), self.dead_zone, self.outer_dead_zone);
+        let mut value = if magnitude == 0.0 { 0.0 } else { value.signum() * magnitude };
+        value = self.curve.apply(value);
+        value *= self.sensitivity;
+        if self.invert {
+            value = -value;
+        }
+        if self.clamp {
+            value = value.clamp(-1.0, 1.0);
+        }
+        value
+    }
+
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        validate_zones(self.dead_zone, self.outer_dead_zone)?;
+        self.curve.validate()
+    }
+
+    fn bits(&self) -> (u32, Option<u32>, u32, bool, bool) {
+        (
+            self.dead_zone.to_bits(),
+            self.outer_dead_zone.map(f32::to_bits),
+            self.sensitivity.to_bits(),
+            self.invert,
+            self.clamp
+        )
+    }
+}
+
+impl PartialEq for AxisProcessing {
+    fn eq(&self, other: &AxisProcessing) -> bool {
+        self.bits() == other.bits() && self.curve == other.curve
+    }
+}
+
+impl Eq for AxisProcessing {}
+
+impl Hash for AxisProcessing {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits().hash(state);
+        self.curve.hash(state);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DualAxisProcessing {
+    pub dead_zone: f32,
+    pub outer_dead_zone: Option<f32>,
+    pub sensitivity: f32,
+    pub invert_y: bool,
+    pub normalize: bool
+}
+
+impl Default for DualAxisProcessing {
+    fn default() -> DualAxisProcessing {
+        DualAxisProcessing {
+            dead_zone: 0.0,
+            outer_dead_zone: None,
+            sensitivity: 1.0,
+            invert_y: false,
+            normalize: false
+        }
+    }
+}
+
+impl DualAxisProcessing {
+    pub fn apply(&self, value: Vec2) -> Vec2 {
+        let length = value.length();
+        let magnitude = rescale(length, self.dead_zone, self.outer_dead_zone);
+        let mut value = if magnitude == 0.0 { Vec2::ZERO } else { value / length * magnitude };
+        value *= self.sensitivity;
+        if self.invert_y {
+            value.y = -value.y;
+        }
+        if self.normalize {
+            value = value.clamp_length_max(1.0);
+        }
+        value
+    }
+
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        validate_zones(self.dead_zone, self.outer_dead_zone)
+    }
+
+    fn bits(&self) -> (u32, Option<u32>, u32, bool, bool) {
+        (
+            self.dead_zone.to_bits(),
+            self.outer_dead_zone.map(f32::to_bits),
+            self.sensitivity.to_bits(),
+            self.invert_y,
+            self.normalize
+        )
+    }
+}
+
+impl PartialEq for DualAxisProcessing {
+    fn eq(&self, other: &DualAxisProcessing) -> bool {
+        self.bits() == other.bits()
+    }
+}
+
+impl Eq for DualAxisProcessing {}
+
+impl Hash for DualAxisProcessing {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn dead_zone_rescales_without_outer_zone() {
+        let settings = AxisProcessing { dead_zone: 0.2, ..Default::default() };
+        assert_eq!(settings.apply(0.2), 0.0);
+        assert!(approx(settings.apply(0.3), 0.125));
+        assert!(approx(settings.apply(-0.6), -0.5));
+        assert!(approx(settings.apply(1.0), 1.0));
+    }
+
+    #[test]
+    fn outer_dead_zone_saturates() {
+        let settings = AxisProcessing { dead_zone: 0.1, outer_dead_zone: Some(0.9), ..Default::default() };
+        assert!(approx(settings.apply(0.5), 0.5));
+        assert_eq!(settings.apply(0.95), 1.0);
+        assert_eq!(settings.apply(-0.95), -1.0);
+    }
+
+    #[test]
+    fn invalid_zones_are_rejected() {
+        assert!(AxisProcessing { dead_zone: 0.5, outer_dead_zone: Some(0.5), ..Default::default() }.validate().is_err());
+        assert!(AxisProcessing { dead_zone: 1.0, ..Default::default() }.validate().is_err());
+        assert!(AxisProcessing { dead_zone: -0.1, ..Default::default() }.validate().is_err());
+        assert!(DualAxisProcessing { dead_zone: f32::NAN, ..Default::default() }.validate().is_err());
+        assert!(AxisProcessing { dead_zone: 0.1, outer_dead_zone: Some(0.9), ..Default::default() }.validate().is_ok());
+        let settings = AxisProcessing { dead_zone: 0.5, outer_dead_zone: Some(0.4), ..Default::default() };
+        assert_eq!(settings.apply(0.45), 0.0);
+        assert_eq!(settings.apply(0.6), 1.0);
+    }
+
+    #[test]
+    fn curve_points_must_be_sorted() {
+        let sorted = ResponseCurve::Points(vec![(0.0, 0.0), (0.5, 0.2), (1.0, 1.0)]);
+        let unsorted = ResponseCurve::Points(vec![(0.0, 0.0), (1.0, 1.0), (0.5, 0.2)]);
+        assert!(sorted.validate().is_ok());
+        assert!(unsorted.validate().is_err());
+        assert!(approx(sorted.apply(0.25), 0.1));
+        assert!(approx(sorted.apply(-0.75), -0.6));
+    }
+
+    #[test]
+    fn exponent_must_be_positive() {
+        assert!(ResponseCurve::Exponential(2.0).validate().is_ok());
+        assert!(ResponseCurve::Exponential(0.0).validate().is_err());
+        assert!(ResponseCurve::Exponential(-1.0).validate().is_err());
+        assert!(ResponseCurve::Exponential(f32::NAN).validate().is_err());
+        assert!(ResponseCurve::Exponential(f32::INFINITY).validate().is_err());
+    }
+
+    #[test]
+    fn dual_dead_zone_is_radial() {
+        let settings = DualAxisProcessing { dead_zone: 0.2, outer_dead_zone: Some(0.6), ..Default::default() };
+        assert_eq!(settings.apply(Vec2::new(0.1, 0.1)), Vec2::ZERO);
+        let value = settings.apply(Vec2::new(0.0, 0.4));
+        assert!(approx(value.x, 0.0) && approx(value.y, 0.5));
+        let value = settings.apply(Vec2::new(0.6, 0.8));
+        assert!(approx(value.length(), 1.0));
+    }
+}
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use crate::{ConfigActions, Event};
+
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
+pub enum CaptureKind {
+    Button,
+    Axis,
+    Any
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Capture<A: ConfigActions> {
+    pub action: A,
+    pub kind: CaptureKind,
+    pub started_at: Option<Duration>
+}
+
+#[derive(Debug)]
+pub struct ActionsRebind<A: ConfigActions> {
+    pub capture: Option<Capture<A>>,
+    pub timeout: Option<Duration>,
+    pub axis_threshold: f32,
+    pub mouse_threshold: f32,
+    pub apply: bool,
+    pub replace: bool
+}
+
+impl <A: ConfigActions>Default for ActionsRebind<A> {
+    fn default() -> ActionsRebind<A> {
+        ActionsRebind {
+            capture: None,
+            timeout: None,
+            axis_threshold: 0.5,
+            mouse_threshold: 10.0,
+            apply: true,
+            replace: true
+        }
+    }
+}
+
+impl <A: ConfigActions>ActionsRebind<A> {
+    pub fn start(&mut self, action: A, kind: CaptureKind) {
+        self.capture = Some(Capture { action, kind, started_at: None });
+    }
+
+    pub fn cancel(&mut self) -> Option<A> {
+        self.capture.take().map(|capture| capture.action)
+    }
+
+    pub fn capturing(&self) -> Option<A> {
+        self.capture.as_ref().map(|capture| capture.action)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum RebindEvent<A: ConfigActions> {
+    Captured {
+        action: A,
+        event: Event,
+        conflicts: Vec<A>
+    },
+    TimedOut(A)
+}
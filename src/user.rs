@@ -0,0 +1,120 @@
+use std::path::{Path, PathBuf};
+use ron::ser::PrettyConfig;
+
+use crate::{ConfigActions, ActionsConfig, LayerBindings};
+
+pub fn user_config_path(path: impl AsRef<Path>) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(path))
+}
+
+#[derive(Debug)]
+pub struct UserBindings<A: ConfigActions> {
+    pub path: Option<PathBuf>,
+    marker: std::marker::PhantomData<A>
+}
+
+impl <A: ConfigActions>Default for UserBindings<A> {
+    fn default() -> UserBindings<A> {
+        UserBindings::new(None)
+    }
+}
+
+impl <A: ConfigActions>UserBindings<A> {
+    pub fn new(path: Option<PathBuf>) -> UserBindings<A> {
+        UserBindings { path, marker: Default::default() }
+    }
+
+    pub fn layer(config: &ActionsConfig<A>) -> LayerBindings<A> {
+        LayerBindings {
+            combine: config.combine_overrides(),
+            ..LayerBindings::replaced(config.overrides())
+        }
+    }
+
+    pub fn to_ron(config: &ActionsConfig<A>) -> Result<String, anyhow::Error> {
+        Ok(ron::ser::to_string_pretty(&Self::layer(config), PrettyConfig::new())?)
+    }
+
+    pub fn serialize(extension: &str, config: &ActionsConfig<A>) -> Result<String, anyhow::Error> {
+        match extension {
+            #[cfg(feature = "toml")]
+            "toml" => Err(anyhow::anyhow!("User bindings can't be saved as TOML, use a .ron, .json or .yaml path")),
+            #[cfg(feature = "json")]
+            "json" => Ok(serde_json::to_string_pretty(&Self::layer(config))?),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Ok(serde_yaml::to_string(&Self::layer(config))?),
+            _ => Self::to_ron(config)
+        }
+    }
+
+    pub fn save(&self, config: &ActionsConfig<A>) -> Result<(), anyhow::Error> {
+        let path = self.path.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No user bindings path configured"))?;
+        let extension = path.extension().and_then(|x|x.to_str()).unwrap_or("ron");
+        let output = Self::serialize(extension, config)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, output)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::keyboard::KeyCode;
+    use bevy::utils::{HashMap, HashSet};
+    use super::*;
+    use crate::{Button, CombinePolicy, ConfigActionsLoader, ConfigLayer, Event};
+    use crate::tests::TestActions;
+
+    fn key(key: KeyCode) -> Event {
+        Event::Pressed(Button::Keyboard(key))
+    }
+
+    fn asset_layer() -> LayerBindings<TestActions> {
+        LayerBindings::added([
+            (TestActions::Jump, vec![key(KeyCode::Space)]),
+            (TestActions::Fire, vec![key(KeyCode::F)])
+        ].into_iter().collect())
+    }
+
+    fn edited_config() -> ActionsConfig<TestActions> {
+        let mut config = ActionsConfig::default();
+        config.push_layer(ConfigLayer::Asset, asset_layer());
+        config.bind(key(KeyCode::Up), TestActions::Jump);
+        config.unbind_action(TestActions::Fire);
+        config.set_combine_policy(TestActions::Jump, CombinePolicy::Sum);
+        config
+    }
+
+    fn bindings(config: &ActionsConfig<TestActions>) -> HashMap<TestActions, HashSet<Event>> {
+        config.bindings().into_iter()
+            .map(|(action, events)| (action, events.into_iter().collect()))
+            .collect()
+    }
+
+    fn assert_round_trip(extension: &str, output: &str) {
+        let config = edited_config();
+        let layer = ConfigActionsLoader::<TestActions>::parse(extension, output.as_bytes()).unwrap();
+        let mut reloaded = ActionsConfig::default();
+        reloaded.push_layer(ConfigLayer::Asset, asset_layer());
+        reloaded.push_layer(ConfigLayer::User, layer);
+        assert_eq!(bindings(&reloaded), bindings(&config));
+        assert_eq!(reloaded.combine_policy(TestActions::Jump), CombinePolicy::Sum);
+        assert_eq!(reloaded.source(&key(KeyCode::Up), TestActions::Jump), Some(ConfigLayer::User));
+    }
+
+    #[test]
+    fn ron_round_trip() {
+        let output = UserBindings::to_ron(&edited_config()).unwrap();
+        assert_round_trip("ron", &output);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_round_trip() {
+        let output = UserBindings::serialize("json", &edited_config()).unwrap();
+        assert_round_trip("json", &output);
+    }
+}